@@ -0,0 +1,43 @@
+# frozen_string_literal: true
+
+require "libfmod"
+
+system = FMOD::Studio::System.create
+system.init(64, 0, 0)
+core = system.get_core_system
+
+system.load_bank_file("media/Master.bank", 0)
+system.load_bank_file("media/Master.strings.bank", 0)
+system.load_bank_file("media/VO.bank", 0)
+
+# Every line of dialogue is a subsound of this FSB.
+dialogue = core.create_sound("media/programmer_sound.fsb", FMOD::Core::Mode::CREATECOMPRESSEDSAMPLE, nil)
+
+mask = FMOD::Studio::EventCallbackType::CREATE_PROGRAMMER_SOUND |
+       FMOD::Studio::EventCallbackType::DESTROY_PROGRAMMER_SOUND
+
+event = system.get_event("event:/Character/Radio/Command")
+event.set_callback(proc { |_event, type, properties|
+  case type
+  when FMOD::Studio::EventCallbackType::CREATE_PROGRAMMER_SOUND
+    properties.sound = dialogue
+    properties.subsound_index = rand(dialogue.get_num_sub_sounds)
+  when FMOD::Studio::EventCallbackType::DESTROY_PROGRAMMER_SOUND
+    # The subsound belongs to the FSB, which we release at the end instead.
+    properties.sound = nil
+  end
+
+  0
+}, mask)
+
+instance = event.create_instance
+instance.start
+instance.release
+
+300.times do
+  system.update
+  sleep(1.0 / 60.0)
+end
+
+dialogue.release
+system.release
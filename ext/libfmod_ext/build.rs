@@ -0,0 +1,32 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of libfmod-rb.
+//
+// libfmod-rb is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// libfmod-rb is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with libfmod-rb.  If not, see <http://www.gnu.org/licenses/>.
+
+fn main() {
+    // Set by libfmod's build script when the FMOD SDK has the fsbank library.
+    println!("cargo:rustc-check-cfg=cfg(fsbank)");
+    if std::env::var_os("DEP_FMOD_FSBANK").is_some() {
+        println!("cargo:rustc-cfg=fsbank");
+    }
+
+    // Set by libfmod's build script on platforms that load FMOD as a shared object.
+    // Embedding these as an rpath means libfmod_ext can find libfmod.so without LD_LIBRARY_PATH.
+    if let Some(lib_dirs) = std::env::var_os("DEP_FMOD_LIB_DIRS") {
+        for dir in std::env::split_paths(&lib_dirs) {
+            println!("cargo:rustc-link-arg-cdylib=-Wl,-rpath,{}", dir.display());
+        }
+    }
+}
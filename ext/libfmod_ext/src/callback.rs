@@ -16,16 +16,31 @@
 // along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
 
 use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+use magnus::rb_sys::FromRawValue;
 use once_cell::sync::Lazy;
-
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crate::core::channel_control::{
+    ChannelControlCallbackData, ChannelControlType, ChannelControlUserData,
+};
+use crate::core::dsp::DspReadState;
+use crate::enums::ChannelControlCallbackType;
 use crate::studio::bank::Bank;
 use crate::studio::command_replay::{CommandCallbackType, CommandUserData};
-use crate::studio::event::{EventCallbackParameterType, EventInstance, EventUserData};
+use crate::studio::event::{
+    EventCallbackParameterType, EventInstance, EventUserData, ProgrammerSoundProperties,
+};
 use crate::studio::system::StudioUserData;
-use crate::thread::{spawn_rb_thread, without_gvl};
+use crate::thread::{gvl_released, spawn_rb_thread, with_gvl, without_gvl, without_gvl_no_ubf};
 
 pub(crate) trait Callback {
     fn call(self: Box<Self>);
+
+    // Whether FMOD is waiting on this callback to finish.
+    fn is_blocking(&self) -> bool {
+        true
+    }
 }
 
 type BoxedCallback = Box<dyn Callback + Send>;
@@ -33,52 +48,237 @@ type CallbackSender = Sender<Option<BoxedCallback>>;
 type CallbackReceiver = Receiver<Option<BoxedCallback>>;
 
 static CHANNEL: Lazy<(CallbackSender, CallbackReceiver)> = Lazy::new(unbounded);
+// Set when the event thread is asked to stop, and cleared when it is restarted.
+// The event thread only stops on a `None` while this is set, so stale `None`s left in CHANNEL are skipped.
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+// Set by `FMOD.callback_dispatch = :update`.
+// Callbacks are then run by whichever Ruby thread calls System#update, instead of the event thread.
+static DISPATCH_ON_UPDATE: AtomicBool = AtomicBool::new(false);
+static UPDATE_QUEUE: Lazy<(Sender<BoxedCallback>, Receiver<BoxedCallback>)> = Lazy::new(unbounded);
+
+// The numbers behind FMOD.callback_stats.
+static PENDING: AtomicUsize = AtomicUsize::new(0);
+static PROCESSED: AtomicU64 = AtomicU64::new(0);
+static TOTAL_LATENCY_NS: AtomicU64 = AtomicU64::new(0);
+
+// Every queued callback is wrapped in this to measure how long it took Ruby to get to it.
+struct Timed {
+    callback: BoxedCallback,
+    queued_at: Instant,
+}
+
+impl Callback for Timed {
+    fn call(self: Box<Self>) {
+        let latency = self.queued_at.elapsed().as_nanos() as u64;
+        PENDING.fetch_sub(1, Ordering::Relaxed);
+        TOTAL_LATENCY_NS.fetch_add(latency, Ordering::Relaxed);
+
+        self.callback.call();
+
+        PROCESSED.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn is_blocking(&self) -> bool {
+        self.callback.is_blocking()
+    }
+}
 
 fn add_callback(callback: BoxedCallback) {
-    #[cfg(feature = "track-callbacks")]
-    println!("Adding callback to queue");
+    let on_update = DISPATCH_ON_UPDATE.load(Ordering::Relaxed);
+    if !on_update && SHUTDOWN.load(Ordering::Acquire) && callback.is_blocking() {
+        // Nothing would run it until the event thread is restarted, and FMOD can't wait that long.
+        // Dropping the callback drops its sender, so whoever is waiting on it gets the default result.
+        return;
+    }
+
+    PENDING.fetch_add(1, Ordering::Relaxed);
+    let callback = Box::new(Timed {
+        callback,
+        queued_at: Instant::now(),
+    });
+
+    if !on_update {
+        CHANNEL.0.send(Some(callback)).unwrap();
+    } else if gvl_released() {
+        // FMOD is calling us from inside System#update (synchronous updates or deferred callbacks).
+        // We're on the Ruby thread that called it, so just take the GVL back and run the callback here.
+        let mut callback = Some(callback);
+        unsafe { with_gvl(|| callback.take().unwrap().call()) }
+    } else {
+        // FMOD's own thread. It'll block until the next System#update runs this.
+        UPDATE_QUEUE.0.send(callback).unwrap();
+    }
+}
+
+// Runs func on a Ruby thread and waits for its result, from any thread.
+// Unlike add_callback this never waits for System#update, as FMOD may need the result to finish a call that is blocking the thread that would update it (like loading a bank).
+// Returns None if func could not be run, because the event thread is shut down.
+pub(crate) fn call_ruby<T, F>(func: F) -> Option<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let mut func = Some(func);
+
+    if unsafe { rb_sys::ruby_native_thread_p() } != 0 {
+        // A Ruby thread, which may or may not have released the GVL.
+        return Some(unsafe { with_gvl(|| func.take().unwrap()()) });
+    }
+
+    if SHUTDOWN.load(Ordering::Acquire) {
+        return None;
+    }
+
+    let (sender, reciever) = bounded(1);
+    let func = func.unwrap();
+    PENDING.fetch_add(1, Ordering::Relaxed);
+    CHANNEL
+        .0
+        .send(Some(Box::new(Timed {
+            callback: Box::new(RubyCall(Box::new(move || sender.send(func()).unwrap()))),
+            queued_at: Instant::now(),
+        })))
+        .unwrap();
+
+    // The event thread dropping func without running it (because it was shut down) disconnects the sender.
+    reciever.recv().ok()
+}
+
+struct RubyCall(Box<dyn FnOnce() + Send>);
+
+impl Callback for RubyCall {
+    fn call(self: Box<Self>) {
+        (self.0)()
+    }
+}
+
+// Runs callbacks queued from FMOD's threads while dispatching on update.
+// Called before and after System#update, so this must be called with the GVL held.
+pub(crate) fn dispatch_queued() {
+    while let Ok(callback) = UPDATE_QUEUE.1.try_recv() {
+        callback.call();
+    }
+}
+
+// Lets FMOD handles (which are just pointers) cross over to the thread in without_gvl_dispatching.
+struct AssumeSend<T>(T);
+
+unsafe impl<T> Send for AssumeSend<T> {}
+
+impl<T> AssumeSend<T> {
+    // Taking self as a whole stops closures from capturing just the (not Send) field.
+    fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+// Like without_gvl_no_ubf, for calls that wait on FMOD's threads (flushing commands, loading banks...).
+// While dispatching on update, those threads may be blocked on a callback that only System#update would run, which
+// this thread can't call until func returns. So func runs on a thread of its own while this one runs the callbacks.
+pub(crate) unsafe fn without_gvl_dispatching<Func, FuncReturn>(func: Func) -> FuncReturn
+where
+    Func: FnOnce() -> FuncReturn,
+{
+    let mut func = Some(func);
+    if !DISPATCH_ON_UPDATE.load(Ordering::Relaxed) {
+        return without_gvl_no_ubf(|| func.take().unwrap()());
+    }
+
+    let func = AssumeSend(func.unwrap());
+    let result = std::thread::scope(|scope| {
+        let (sender, reciever) = bounded(1);
+        scope.spawn(move || {
+            let func = func.into_inner();
+            sender.send(AssumeSend(func())).unwrap();
+        });
+
+        loop {
+            let next = without_gvl_no_ubf(|| {
+                crossbeam_channel::select! {
+                    recv(UPDATE_QUEUE.1) -> callback => Err(callback.unwrap()),
+                    recv(reciever) -> result => Ok(result.ok()),
+                }
+            });
+
+            match next {
+                Ok(result) => break result,
+                Err(callback) => callback.call(),
+            }
+        }
+    });
+
+    // None means func panicked, in which case the scope has already re-raised it.
+    result.unwrap().into_inner()
+}
 
-    CHANNEL.0.send(Some(callback)).unwrap();
+fn callback_dispatch() -> magnus::Symbol {
+    if DISPATCH_ON_UPDATE.load(Ordering::Relaxed) {
+        magnus::Symbol::new("update")
+    } else {
+        magnus::Symbol::new("thread")
+    }
+}
+
+fn set_callback_dispatch(mode: magnus::Symbol) -> Result<magnus::Symbol, magnus::Error> {
+    let on_update = match mode.name()?.as_ref() {
+        "thread" => false,
+        "update" => true,
+        _ => {
+            return Err(magnus::Error::new(
+                magnus::exception::arg_error(),
+                "callback dispatch must be :thread or :update",
+            ))
+        }
+    };
+    DISPATCH_ON_UPDATE.store(on_update, Ordering::Relaxed);
+
+    Ok(mode)
+}
+
+pub fn bind(module: magnus::RModule) -> Result<(), magnus::Error> {
+    use magnus::Module;
+
+    module.const_set("EventThread", spawn_event_thread())?;
+    module.define_module_function(
+        "shutdown_event_thread",
+        magnus::function!(shutdown_event_thread, 0),
+    )?;
+    module.define_module_function(
+        "restart_event_thread",
+        magnus::function!(restart_event_thread, 0),
+    )?;
+    module.define_module_function("callback_stats", magnus::function!(callback_stats, 0))?;
+
+    module.define_module_function("callback_dispatch", magnus::function!(callback_dispatch, 0))?;
+    module.define_module_function(
+        "callback_dispatch=",
+        magnus::function!(set_callback_dispatch, 1),
+    )?;
+
+    Ok(())
 }
 
 // Unsafety galore!
-pub fn callback_thread() -> u64 {
+fn callback_thread() -> u64 {
     loop {
         let callback = unsafe {
             without_gvl(
+                || CHANNEL.1.recv().unwrap(),
                 || {
-                    #[cfg(feature = "track-callbacks")]
-                    println!("Waiting for a callback to run...");
-
-                    CHANNEL.1.recv().unwrap()
-                },
-                || {
-                    #[cfg(feature = "track-callbacks")]
-                    println!("Aborting callback thread...");
-
-                    // Send a `None` to let notify that we're aborting.
+                    // Ruby wants this thread to stop, so wake it up with a `None`.
+                    SHUTDOWN.store(true, Ordering::Release);
                     CHANNEL.0.send(None).unwrap();
                 },
             )
         };
 
-        #[cfg(feature = "track-callbacks")]
-        println!("A callback needs to be run.");
-
-        #[cfg(feature = "track-callbacks")]
-        println!("Attempting to spawn a thread for the callback.");
-
         // Get the callback we need to run.
         if let Some(callback) = callback {
-            #[cfg(feature = "track-callbacks")]
-            println!("Spawning a thread to run callback");
-
             unsafe {
                 // This function handles passing the callback over the ffi boundary.
                 // It boxes it...
                 spawn_rb_thread(move || {
-                    #[cfg(feature = "track-callbacks")]
-                    println!("Attempting ro run callback...");
                     // ..Then we call it.
                     callback.call();
                     // The callback should be dropped and we don't have to worry about a memory leak. Hooray!
@@ -86,8 +286,7 @@ pub fn callback_thread() -> u64 {
                     rb_sys::Qnil.into()
                 });
             }
-        } else {
-            println!("Callback EventThread termination requested");
+        } else if SHUTDOWN.load(Ordering::Acquire) {
             break;
         }
     }
@@ -95,6 +294,131 @@ pub fn callback_thread() -> u64 {
     rb_sys::Qnil.into()
 }
 
+fn spawn_event_thread() -> magnus::Value {
+    unsafe { magnus::Value::from_raw(spawn_rb_thread(callback_thread)) }
+}
+
+fn event_thread() -> Result<magnus::Value, magnus::Error> {
+    use magnus::Module;
+
+    let fmod: magnus::RModule = magnus::class::object().const_get("FMOD")?;
+    fmod.const_get("EventThread")
+}
+
+// Stops the event thread once it has picked up every callback queued before this.
+// Afterwards, callbacks FMOD waits on fail straight away with their default result, and the rest wait for FMOD.restart_event_thread.
+fn shutdown_event_thread() -> Result<(), magnus::Error> {
+    let thread = event_thread()?;
+
+    SHUTDOWN.store(true, Ordering::Release);
+    if thread.funcall::<_, _, bool>("alive?", ())? {
+        CHANNEL.0.send(None).unwrap();
+        let _: magnus::Value = thread.funcall("join", ())?;
+    }
+
+    Ok(())
+}
+
+// Shuts down the event thread if it is running, and replaces FMOD::EventThread with a new one.
+fn restart_event_thread() -> Result<magnus::Value, magnus::Error> {
+    use magnus::Module;
+
+    shutdown_event_thread()?;
+
+    let fmod: magnus::RModule = magnus::class::object().const_get("FMOD")?;
+    let _: magnus::Value = fmod.funcall("remove_const", (magnus::Symbol::new("EventThread"),))?;
+
+    // Any `None` still queued was meant for the old thread, and the new one skips it now that this is cleared.
+    SHUTDOWN.store(false, Ordering::Release);
+    let thread = spawn_event_thread();
+    fmod.const_set("EventThread", thread)?;
+
+    Ok(thread)
+}
+
+fn callback_stats() -> Result<magnus::RHash, magnus::Error> {
+    let processed = PROCESSED.load(Ordering::Relaxed);
+    let total_latency = TOTAL_LATENCY_NS.load(Ordering::Relaxed);
+    // Callbacks that have started count towards the latency, so this is slightly off while callbacks are running.
+    let average_latency = if processed == 0 {
+        0.0
+    } else {
+        total_latency as f64 / processed as f64 / 1_000_000_000.0
+    };
+
+    let stats = magnus::RHash::new();
+    stats.aset(
+        magnus::Symbol::new("pending"),
+        PENDING.load(Ordering::Relaxed),
+    )?;
+    stats.aset(magnus::Symbol::new("processed"), processed)?;
+    stats.aset(magnus::Symbol::new("average_latency"), average_latency)?;
+
+    Ok(stats)
+}
+
+// Exceptions raised by callbacks are handled according to FMOD.callback_error_policy (see lib/libfmod/callback_errors.rb).
+// This has to be called from a Ruby thread.
+pub(crate) fn report_error(error: magnus::Error) {
+    let message = error.to_string();
+
+    if let Err(e) = error_to_exception(error).and_then(handle_exception) {
+        eprintln!("WARNING RUBY ERROR IN CALLBACK: {message}");
+        eprintln!("WARNING RUBY ERROR WHILE HANDLING IT: {e}");
+    }
+}
+
+fn error_to_exception(error: magnus::Error) -> Result<magnus::Exception, magnus::Error> {
+    use magnus::Class;
+
+    match error {
+        magnus::Error::Exception(exception) => Ok(exception),
+        magnus::Error::Error(class, message) => class.new_instance((message.into_owned(),)),
+        jump => Err(jump),
+    }
+}
+
+fn handle_exception(exception: magnus::Exception) -> Result<(), magnus::Error> {
+    use magnus::{Module, Object};
+
+    let fmod: magnus::RModule = magnus::class::object().const_get("FMOD")?;
+    let policy: magnus::Value = fmod.ivar_get("@callback_error_policy")?;
+
+    if policy.respond_to("call", false)? {
+        let _: magnus::Value = policy.funcall("call", (exception,))?;
+    } else if policy.respond_to("error", false)? {
+        // Probably a Logger.
+        let _: magnus::Value = policy.funcall("error", (exception,))?;
+    } else if magnus::Symbol::from_value(policy)
+        .map(|s| s.name())
+        .transpose()?
+        .as_deref()
+        == Some("raise")
+    {
+        let pending: magnus::RArray = fmod.ivar_get("@pending_callback_errors")?;
+        pending.push(exception)?;
+    } else {
+        let message = format!("WARNING RUBY ERROR IN CALLBACK: {}", exception.inspect());
+        let _: magnus::Value = magnus::module::kernel().funcall("warn", (message,))?;
+    }
+
+    Ok(())
+}
+
+// Raises the oldest exception a callback raised while FMOD.callback_error_policy was :raise.
+// Called at the end of System#update, so callback errors surface on the thread driving FMOD.
+pub(crate) fn raise_pending_error() -> Result<(), magnus::Error> {
+    use magnus::{Module, Object};
+
+    let fmod: magnus::RModule = magnus::class::object().const_get("FMOD")?;
+    let pending: magnus::RArray = fmod.ivar_get("@pending_callback_errors")?;
+
+    match pending.shift::<Option<magnus::Exception>>()? {
+        Some(exception) => Err(exception.into()),
+        None => Ok(()),
+    }
+}
+
 pub(crate) struct StudioSystemCallback {
     system: crate::studio::system::Studio,
     type_: u32,
@@ -121,26 +445,14 @@ impl StudioSystemCallback {
             user_data,
         });
 
-        #[cfg(feature = "track-callbacks")]
-        println!("System callback created");
-
         add_callback(callback);
 
         reciever
     }
 }
 
-#[cfg(feature = "track-callbacks")]
-impl Drop for StudioSystemCallback {
-    fn drop(&mut self) {
-        println!("Callback has been dropped")
-    }
-}
-
 impl Callback for StudioSystemCallback {
     fn call(self: Box<Self>) {
-        #[cfg(feature = "track-callbacks")]
-        println!("Running callback...");
         let callback = self.user_data.callback.as_deref().copied().unwrap();
 
         let result = callback
@@ -154,13 +466,10 @@ impl Callback for StudioSystemCallback {
                 ),
             )
             .unwrap_or_else(|e| {
-                println!("WARNING RUBY ERROR IN CALLBACK: {e}");
+                report_error(e);
                 0
             });
 
-        #[cfg(feature = "track-callbacks")]
-        println!("Callback finished with result {result}");
-
         self.sender.send(result).unwrap();
     }
 }
@@ -168,7 +477,8 @@ impl Callback for StudioSystemCallback {
 pub(crate) struct EventCallback {
     event: EventInstance,
     type_: u32,
-    sender: Sender<i32>,
+    // None for deferred callbacks, as nothing is waiting on them.
+    sender: Option<Sender<i32>>,
     parameter: EventCallbackParameterType,
     user_data: &'static mut EventUserData,
 }
@@ -190,29 +500,62 @@ impl EventCallback {
             type_,
             parameter,
             user_data,
-            sender,
+            sender: Some(sender),
         });
 
         add_callback(callback);
 
         reciever
     }
+
+    // Queues the callback without waiting on it. The user data is kept alive until it has run.
+    pub fn create_deferred(
+        event: EventInstance,
+        type_: u32,
+        parameter: EventCallbackParameterType,
+        user_data: &'static mut EventUserData,
+    ) {
+        user_data
+            .in_flight
+            .fetch_add(1, std::sync::atomic::Ordering::AcqRel);
+
+        add_callback(Box::new(Self {
+            event,
+            type_,
+            parameter,
+            user_data,
+            sender: None,
+        }));
+    }
 }
 
 impl Callback for EventCallback {
+    fn is_blocking(&self) -> bool {
+        self.sender.is_some()
+    }
+
     fn call(self: Box<Self>) {
         use crate::wrap::WrapFMOD;
 
         let callback = self.user_data.callback.as_deref().copied().unwrap();
+        let parameter: magnus::Value = self.parameter.wrap_fmod();
 
         let result = callback
-            .funcall("call", (self.event, self.type_, self.parameter.wrap_fmod()))
+            .funcall("call", (self.event, self.type_, parameter))
             .unwrap_or_else(|e| {
-                println!("WARNING RUBY ERROR IN CALLBACK: {e}");
+                report_error(e);
                 0
             });
 
-        self.sender.send(result).unwrap();
+        // FMOD is done with these once we return.
+        if let Ok(properties) = parameter.try_convert::<&ProgrammerSoundProperties>() {
+            properties.invalidate();
+        }
+
+        match self.sender {
+            Some(sender) => sender.send(result).unwrap(),
+            None => unsafe { EventUserData::finish_deferred(self.user_data) },
+        }
     }
 }
 
@@ -281,7 +624,7 @@ impl Callback for CommandReplayCallback {
                         result
                     }
                     Err(e) => {
-                        println!("WARNING RUBY ERROR IN CALLBACK: {e}");
+                        report_error(e);
 
                         0
                     }
@@ -296,7 +639,7 @@ impl Callback for CommandReplayCallback {
                     callback.funcall("call", (replay.wrap_fmod(), commandindex, time, userdata));
 
                 result.unwrap_or_else(|e| {
-                    println!("WARNING RUBY ERROR IN CALLBACK: {e}");
+                    report_error(e);
 
                     0
                 })
@@ -333,7 +676,7 @@ impl Callback for CommandReplayCallback {
                         result
                     }
                     Err(e) => {
-                        println!("WARNING RUBY ERROR IN CALLBACK: {e}");
+                        report_error(e);
 
                         0
                     }
@@ -344,3 +687,138 @@ impl Callback for CommandReplayCallback {
         self.sender.send(result).unwrap();
     }
 }
+
+pub(crate) struct ChannelControlCallback {
+    control: ChannelControlType,
+    type_: ChannelControlCallbackType,
+    data: ChannelControlCallbackData,
+    sender: Sender<i32>,
+    user_data: &'static mut ChannelControlUserData,
+}
+
+unsafe impl Send for ChannelControlCallback {}
+
+impl ChannelControlCallback {
+    pub fn create(
+        control: ChannelControlType,
+        type_: ChannelControlCallbackType,
+        data: ChannelControlCallbackData,
+        user_data: &'static mut ChannelControlUserData,
+    ) -> Receiver<i32> {
+        let (sender, reciever) = bounded(1);
+
+        let callback = Box::new(Self {
+            control,
+            type_,
+            data,
+            sender,
+            user_data,
+        });
+
+        add_callback(callback);
+
+        reciever
+    }
+}
+
+impl Callback for ChannelControlCallback {
+    fn call(self: Box<Self>) {
+        use crate::wrap::WrapFMOD;
+
+        let callback = self.user_data.callback.as_deref().copied().unwrap();
+
+        let result = callback
+            .funcall(
+                "call",
+                (
+                    self.control.wrap_fmod(),
+                    self.type_,
+                    self.data.wrap_fmod(),
+                    self.user_data.userdata.as_deref().copied(),
+                ),
+            )
+            .unwrap_or_else(|e| {
+                report_error(e);
+                0
+            });
+
+        self.sender.send(result).unwrap();
+    }
+}
+
+// Unlike every other callback, nothing waits on this one to finish.
+pub(crate) struct DspReadCallback {
+    dsp: libfmod::Dsp,
+    buffer: Vec<f32>,
+    channels: i32,
+    state: std::sync::Arc<DspReadState>,
+}
+
+unsafe impl Send for DspReadCallback {}
+
+impl DspReadCallback {
+    pub fn create(
+        dsp: libfmod::Dsp,
+        buffer: Vec<f32>,
+        channels: i32,
+        state: std::sync::Arc<DspReadState>,
+    ) {
+        add_callback(Box::new(Self {
+            dsp,
+            buffer,
+            channels,
+            state,
+        }));
+    }
+}
+
+impl Callback for DspReadCallback {
+    fn is_blocking(&self) -> bool {
+        false
+    }
+
+    fn call(self: Box<Self>) {
+        use crate::wrap::WrapFMOD;
+
+        let callback = self.state.callback.as_deref().copied().unwrap();
+
+        let result: Result<magnus::Value, _> = callback.funcall(
+            "call",
+            (
+                magnus::Value::from(self.dsp.wrap_fmod()),
+                self.buffer,
+                self.channels,
+            ),
+        );
+        if let Err(e) = result {
+            report_error(e);
+        }
+
+        self.state
+            .pending
+            .fetch_sub(1, std::sync::atomic::Ordering::AcqRel);
+    }
+}
+
+// User data holds BoxValues, and unrooting those needs the GVL, which FMOD's threads do not have.
+// So instead of dropping user data where FMOD tells us it is destroyed, we hand it over to be dropped here.
+pub(crate) struct UserDataRelease(#[allow(dead_code)] Box<dyn std::any::Any>);
+
+// The user data is never touched again by whoever sent it.
+unsafe impl Send for UserDataRelease {}
+
+impl UserDataRelease {
+    pub fn create<T: 'static>(user_data: Box<T>) {
+        add_callback(Box::new(Self(user_data)));
+    }
+}
+
+impl Callback for UserDataRelease {
+    fn is_blocking(&self) -> bool {
+        false
+    }
+
+    fn call(self: Box<Self>) {
+        drop(self);
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of libfmod.
+//
+// libfmod is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// libfmod is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::core::channel_group::ChannelGroup;
+use crate::core::sound::Sound;
+#[allow(unused_imports)]
+use crate::{
+    bind_fn, channel_control, opaque_struct, opaque_struct_function, opaque_struct_method,
+};
+
+opaque_struct!(Channel, "Core", "Channel");
+
+channel_control!(Channel, "Channel", Channel);
+
+impl Channel {
+    opaque_struct_method!(get_frequency, f32;);
+    opaque_struct_method!(set_frequency, (); (f32));
+    opaque_struct_method!(get_priority, i32;);
+    opaque_struct_method!(set_priority, (); (i32));
+    opaque_struct_method!(get_position, u32; (u32));
+    opaque_struct_method!(set_position, (); (u32), (u32));
+    opaque_struct_method!(get_loop_count, i32;);
+    opaque_struct_method!(set_loop_count, (); (i32));
+    opaque_struct_method!(get_loop_points, (u32, u32); (u32), (u32));
+    opaque_struct_method!(set_loop_points, (); (u32), (u32), (u32), (u32));
+    opaque_struct_method!(is_virtual, bool;);
+    opaque_struct_method!(get_channel_group, ChannelGroup;);
+    opaque_struct_method!(set_channel_group, (); (&ChannelGroup));
+    opaque_struct_method!(get_current_sound, Sound;);
+    opaque_struct_method!(get_index, i32;);
+
+    bind_fn! {
+        Channel, "Channel";
+        (get_frequency, method, 0),
+        (set_frequency, method, 1),
+        (get_priority, method, 0),
+        (set_priority, method, 1),
+        (get_position, method, 1),
+        (set_position, method, 2),
+        (get_loop_count, method, 0),
+        (set_loop_count, method, 1),
+        (get_loop_points, method, 2),
+        (set_loop_points, method, 4),
+        (is_virtual, method, 0),
+        (get_channel_group, method, 0),
+        (set_channel_group, method, 1),
+        (get_current_sound, method, 0),
+        (get_index, method, 0)
+    }
+}
+
+pub fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    Channel::bind(module)?;
+    Channel::bind_channel_control(module)
+}
@@ -0,0 +1,457 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of libfmod.
+//
+// libfmod is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// libfmod is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
+
+use magnus::value::BoxValue;
+
+use crate::callback::{ChannelControlCallback, UserDataRelease};
+
+#[derive(Default)]
+pub struct ChannelControlUserData {
+    pub callback: Option<BoxValue<magnus::Value>>,
+    pub userdata: Option<BoxValue<magnus::Value>>,
+}
+
+// FMOD hands callbacks a FMOD_CHANNELCONTROL and tells us what it actually is.
+pub enum ChannelControlType {
+    Channel(libfmod::Channel),
+    ChannelGroup(libfmod::ChannelGroup),
+}
+
+impl crate::wrap::WrapFMOD<magnus::Value> for ChannelControlType {
+    fn wrap_fmod(self) -> magnus::Value {
+        match self {
+            Self::Channel(c) => magnus::Value::from(c.wrap_fmod()),
+            Self::ChannelGroup(c) => magnus::Value::from(c.wrap_fmod()),
+        }
+    }
+}
+
+impl ChannelControlType {
+    unsafe fn get_user_data(&self) -> Option<&'static mut ChannelControlUserData> {
+        let ptr = match self {
+            Self::Channel(c) => c.get_user_data().ok()?,
+            Self::ChannelGroup(c) => c.get_user_data().ok()?,
+        };
+
+        (ptr as *mut ChannelControlUserData).as_mut()
+    }
+}
+
+pub enum ChannelControlCallbackData {
+    None,
+    VirtualVoice(bool),
+    SyncPoint(i32),
+    Occlusion(f32, f32),
+}
+
+impl crate::wrap::WrapFMOD<magnus::Value> for ChannelControlCallbackData {
+    fn wrap_fmod(self) -> magnus::Value {
+        match self {
+            Self::None => *magnus::QNIL,
+            Self::VirtualVoice(to_virtual) => magnus::Value::from(to_virtual),
+            Self::SyncPoint(index) => magnus::Value::from(index),
+            Self::Occlusion(direct, reverb) => *magnus::RArray::from_vec(vec![direct, reverb]),
+        }
+    }
+}
+
+pub(crate) unsafe extern "C" fn channel_control_callback(
+    channelcontrol: *mut libfmod::ffi::FMOD_CHANNELCONTROL,
+    controltype: libfmod::ffi::FMOD_CHANNELCONTROL_TYPE,
+    callbacktype: libfmod::FMOD_CHANNELCONTROL_CALLBACK_TYPE,
+    commanddata1: *mut std::ffi::c_void,
+    commanddata2: *mut std::ffi::c_void,
+) -> i32 {
+    use crate::wrap::WrapFMOD;
+
+    let control = match controltype {
+        libfmod::ffi::FMOD_CHANNELCONTROL_CHANNEL => {
+            ChannelControlType::Channel(libfmod::Channel::from(channelcontrol as *mut _))
+        }
+        libfmod::ffi::FMOD_CHANNELCONTROL_CHANNELGROUP => {
+            ChannelControlType::ChannelGroup(libfmod::ChannelGroup::from(channelcontrol as *mut _))
+        }
+        _ => return libfmod::ffi::FMOD_OK,
+    };
+
+    // No user data means no callback was set through us.
+    let Some(user_data) = control.get_user_data() else {
+        return libfmod::ffi::FMOD_OK;
+    };
+
+    let data = match callbacktype {
+        libfmod::FMOD_CHANNELCONTROL_CALLBACK_TYPE::FMOD_CHANNELCONTROL_CALLBACK_VIRTUALVOICE => {
+            ChannelControlCallbackData::VirtualVoice(commanddata1 as usize != 0)
+        }
+        libfmod::FMOD_CHANNELCONTROL_CALLBACK_TYPE::FMOD_CHANNELCONTROL_CALLBACK_SYNCPOINT => {
+            ChannelControlCallbackData::SyncPoint(commanddata1 as i32)
+        }
+        libfmod::FMOD_CHANNELCONTROL_CALLBACK_TYPE::FMOD_CHANNELCONTROL_CALLBACK_OCCLUSION => {
+            ChannelControlCallbackData::Occlusion(
+                *(commanddata1 as *mut f32),
+                *(commanddata2 as *mut f32),
+            )
+        }
+        _ => ChannelControlCallbackData::None,
+    };
+
+    let user_data_ptr = user_data as *mut ChannelControlUserData;
+
+    // User data is also created by set_user_data, which installs this callback without a Ruby one to call.
+    let result = if user_data.callback.is_none() {
+        libfmod::ffi::FMOD_OK
+    } else {
+        let reciever =
+            ChannelControlCallback::create(control, callbacktype.wrap_fmod(), data, user_data);
+
+        reciever.recv().unwrap_or_else(|e| {
+            eprintln!("Warning callback recv error: {e}");
+            0
+        })
+    };
+
+    // A channel is gone for good once it ends, and so is its user data.
+    // Any callback using it has finished by now as we wait on them.
+    if controltype == libfmod::ffi::FMOD_CHANNELCONTROL_CHANNEL
+        && matches!(
+            callbacktype,
+            libfmod::FMOD_CHANNELCONTROL_CALLBACK_TYPE::FMOD_CHANNELCONTROL_CALLBACK_END
+        )
+    {
+        let channel = libfmod::Channel::from(channelcontrol as *mut _);
+        if channel.set_user_data(std::ptr::null_mut()).is_ok() {
+            UserDataRelease::create(Box::from_raw(user_data_ptr));
+        }
+    }
+
+    result
+}
+
+// Channel and ChannelGroup share the entire ChannelControl API, with the only difference being the ffi prefix.
+// This generates those methods on $name, along with a `bind_channel_control` function to bind them.
+#[macro_export]
+macro_rules! channel_control {
+    ($name:ident, $rb_name:literal, $ffi_name:ident) => {
+        impl $name {
+            $crate::opaque_struct_method!(get_system_object, $crate::core::system::System;);
+            $crate::opaque_struct_method!(stop, (););
+            $crate::opaque_struct_method!(get_paused, bool;);
+            $crate::opaque_struct_method!(set_paused, (); (bool));
+            $crate::opaque_struct_method!(get_volume, f32;);
+            $crate::opaque_struct_method!(set_volume, (); (f32));
+            $crate::opaque_struct_method!(get_volume_ramp, bool;);
+            $crate::opaque_struct_method!(set_volume_ramp, (); (bool));
+            $crate::opaque_struct_method!(get_audibility, f32;);
+            $crate::opaque_struct_method!(get_pitch, f32;);
+            $crate::opaque_struct_method!(set_pitch, (); (f32));
+            $crate::opaque_struct_method!(get_mute, bool;);
+            $crate::opaque_struct_method!(set_mute, (); (bool));
+            $crate::opaque_struct_method!(get_reverb_properties, f32; (i32));
+            $crate::opaque_struct_method!(set_reverb_properties, (); (i32), (f32));
+            $crate::opaque_struct_method!(get_low_pass_gain, f32;);
+            $crate::opaque_struct_method!(set_low_pass_gain, (); (f32));
+            $crate::opaque_struct_method!(get_mode, u32;);
+            $crate::opaque_struct_method!(set_mode, (); (u32));
+            $crate::opaque_struct_method!(is_playing, bool;);
+            $crate::opaque_struct_method!(set_pan, (); (f32));
+            $crate::opaque_struct_method!(
+                set_mix_levels_output, ();
+                (f32), (f32), (f32), (f32), (f32), (f32), (f32), (f32)
+            );
+            $crate::opaque_struct_method!(get_dsp_clock, (u64, u64););
+            $crate::opaque_struct_method!(get_delay, (u64, u64, bool););
+            $crate::opaque_struct_method!(set_delay, (); (u64), (u64), (bool));
+            $crate::opaque_struct_method!(add_fade_point, (); (u64), (f32));
+            $crate::opaque_struct_method!(set_fade_point_ramp, (); (u64), (f32));
+            $crate::opaque_struct_method!(remove_fade_points, (); (u64), (u64));
+            $crate::opaque_struct_method!(get_3d_attributes, (magnus::RStruct, magnus::RStruct););
+            $crate::opaque_struct_method!(
+                set_3d_attributes, ();
+                (Option<magnus::RStruct>), (Option<magnus::RStruct>)
+            );
+            $crate::opaque_struct_method!(get_3d_min_max_distance, (f32, f32););
+            $crate::opaque_struct_method!(set_3d_min_max_distance, (); (f32), (f32));
+            $crate::opaque_struct_method!(get_3d_cone_settings, (f32, f32, f32););
+            $crate::opaque_struct_method!(set_3d_cone_settings, (); (f32), (f32), (f32));
+            $crate::opaque_struct_method!(get_3d_occlusion, (f32, f32););
+            $crate::opaque_struct_method!(set_3d_occlusion, (); (f32), (f32));
+            $crate::opaque_struct_method!(get_3d_spread, f32;);
+            $crate::opaque_struct_method!(set_3d_spread, (); (f32));
+            $crate::opaque_struct_method!(get_3d_level, f32;);
+            $crate::opaque_struct_method!(set_3d_level, (); (f32));
+            $crate::opaque_struct_method!(get_3d_doppler_level, f32;);
+            $crate::opaque_struct_method!(set_3d_doppler_level, (); (f32));
+            $crate::opaque_struct_method!(get_num_dsps, i32;);
+            $crate::opaque_struct_method!(get_dsp, $crate::core::dsp::Dsp; (i32));
+            $crate::opaque_struct_method!(add_dsp, (); (i32), (&$crate::core::dsp::Dsp));
+            $crate::opaque_struct_method!(remove_dsp, (); (&$crate::core::dsp::Dsp));
+            $crate::opaque_struct_method!(get_dsp_index, i32; (&$crate::core::dsp::Dsp));
+            $crate::opaque_struct_method!(set_dsp_index, (); (&$crate::core::dsp::Dsp), (i32));
+
+            fn set_mix_levels_input(&self, mut levels: Vec<f32>) -> Result<(), magnus::Error> {
+                paste::paste! {
+                    unsafe {
+                        match libfmod::ffi::[<FMOD_ $ffi_name _SetMixLevelsInput>](
+                            self.0.as_mut_ptr(),
+                            levels.as_mut_ptr(),
+                            levels.len() as _,
+                        ) {
+                            libfmod::ffi::FMOD_OK => Ok(()),
+                            err => Err($crate::err_fmod!(
+                                concat!("FMOD_", stringify!($ffi_name), "_SetMixLevelsInput"),
+                                err
+                            )),
+                        }
+                    }
+                }
+            }
+
+            // The matrix is a flat array of outchannels * inchannels.
+            fn get_mix_matrix(&self) -> Result<(Vec<f32>, i32, i32), magnus::Error> {
+                paste::paste! {
+                    unsafe {
+                        let mut outchannels = 0;
+                        let mut inchannels = 0;
+
+                        let result = libfmod::ffi::[<FMOD_ $ffi_name _GetMixMatrix>](
+                            self.0.as_mut_ptr(),
+                            std::ptr::null_mut(),
+                            &mut outchannels,
+                            &mut inchannels,
+                            0,
+                        );
+
+                        match result {
+                            libfmod::ffi::FMOD_OK => {
+                                let mut matrix = vec![0.0; (outchannels * inchannels) as usize];
+
+                                match libfmod::ffi::[<FMOD_ $ffi_name _GetMixMatrix>](
+                                    self.0.as_mut_ptr(),
+                                    matrix.as_mut_ptr(),
+                                    &mut outchannels,
+                                    &mut inchannels,
+                                    0,
+                                ) {
+                                    libfmod::ffi::FMOD_OK => Ok((matrix, outchannels, inchannels)),
+                                    err => Err($crate::err_fmod!(
+                                        concat!("FMOD_", stringify!($ffi_name), "_GetMixMatrix"),
+                                        err
+                                    )),
+                                }
+                            }
+                            err => Err($crate::err_fmod!(
+                                concat!("FMOD_", stringify!($ffi_name), "_GetMixMatrix"),
+                                err
+                            )),
+                        }
+                    }
+                }
+            }
+
+            fn set_mix_matrix(
+                &self,
+                mut matrix: Vec<f32>,
+                outchannels: i32,
+                inchannels: i32,
+            ) -> Result<(), magnus::Error> {
+                if matrix.len() != (outchannels * inchannels) as usize {
+                    return Err(magnus::Error::new(
+                        magnus::exception::arg_error(),
+                        format!(
+                            "expected a matrix of {} elements but it was {}",
+                            outchannels * inchannels,
+                            matrix.len()
+                        ),
+                    ));
+                }
+
+                paste::paste! {
+                    unsafe {
+                        match libfmod::ffi::[<FMOD_ $ffi_name _SetMixMatrix>](
+                            self.0.as_mut_ptr(),
+                            matrix.as_mut_ptr(),
+                            outchannels,
+                            inchannels,
+                            0,
+                        ) {
+                            libfmod::ffi::FMOD_OK => Ok(()),
+                            err => Err($crate::err_fmod!(
+                                concat!("FMOD_", stringify!($ffi_name), "_SetMixMatrix"),
+                                err
+                            )),
+                        }
+                    }
+                }
+            }
+
+            // Returns the dsp clocks and volumes of every fade point as two arrays.
+            fn get_fade_points(&self) -> Result<(Vec<u64>, Vec<f32>), magnus::Error> {
+                paste::paste! {
+                    unsafe {
+                        let mut count = 0;
+
+                        let result = libfmod::ffi::[<FMOD_ $ffi_name _GetFadePoints>](
+                            self.0.as_mut_ptr(),
+                            &mut count,
+                            std::ptr::null_mut(),
+                            std::ptr::null_mut(),
+                        );
+
+                        match result {
+                            libfmod::ffi::FMOD_OK => {
+                                let mut clocks = vec![0; count as usize];
+                                let mut volumes = vec![0.0; count as usize];
+
+                                match libfmod::ffi::[<FMOD_ $ffi_name _GetFadePoints>](
+                                    self.0.as_mut_ptr(),
+                                    &mut count,
+                                    clocks.as_mut_ptr(),
+                                    volumes.as_mut_ptr(),
+                                ) {
+                                    libfmod::ffi::FMOD_OK => Ok((clocks, volumes)),
+                                    err => Err($crate::err_fmod!(
+                                        concat!("FMOD_", stringify!($ffi_name), "_GetFadePoints"),
+                                        err
+                                    )),
+                                }
+                            }
+                            err => Err($crate::err_fmod!(
+                                concat!("FMOD_", stringify!($ffi_name), "_GetFadePoints"),
+                                err
+                            )),
+                        }
+                    }
+                }
+            }
+
+            fn set_callback(&self, callback: magnus::Value) -> Result<(), magnus::Error> {
+                self.get_or_create_user_data()?.callback = Some(magnus::value::BoxValue::new(callback));
+
+                Ok(())
+            }
+
+            fn get_user_data(&self) -> Result<Option<magnus::Value>, magnus::Error> {
+                self.get_or_create_user_data()
+                    .map(|userdata| userdata.userdata.as_ref().map(|b| **b))
+            }
+
+            fn set_user_data(&self, val: Option<magnus::Value>) -> Result<(), magnus::Error> {
+                self.get_or_create_user_data().map(|userdata| {
+                    userdata.userdata = val.map(magnus::value::BoxValue::new);
+                })
+            }
+
+            fn get_or_create_user_data(
+                &self,
+            ) -> Result<&mut $crate::core::channel_control::ChannelControlUserData, magnus::Error> {
+                use $crate::core::channel_control::ChannelControlUserData;
+                use $crate::wrap::WrapFMOD;
+
+                let ptr =
+                    self.0.get_user_data().map_err(|e| e.wrap_fmod())? as *mut ChannelControlUserData;
+
+                if let Some(user_data) = unsafe { ptr.as_mut() } {
+                    return Ok(user_data);
+                }
+
+                // The callback frees the user data once a channel ends, so it goes in even without a Ruby one.
+                let raw_ptr: *mut ChannelControlUserData = Box::into_raw(Box::default());
+                if let Err(e) = self.0.set_user_data(raw_ptr as *mut _).and_then(|_| {
+                    self.0
+                        .set_callback(Some($crate::core::channel_control::channel_control_callback))
+                }) {
+                    let _ = self.0.set_user_data(std::ptr::null_mut());
+                    drop(unsafe { Box::from_raw(raw_ptr) });
+                    return Err(e.wrap_fmod());
+                }
+
+                Ok(unsafe { &mut *raw_ptr })
+            }
+
+            fn bind_channel_control(module: impl magnus::Module) -> Result<(), magnus::Error> {
+                use magnus::method;
+                use magnus::Module;
+
+                // This just fetches the class that `bind` defined.
+                let class = module.define_class($rb_name, Default::default())?;
+
+                // Special indices for the DSP chain.
+                class.const_set("DSP_HEAD", libfmod::ffi::FMOD_CHANNELCONTROL_DSP_HEAD)?;
+                class.const_set("DSP_FADER", libfmod::ffi::FMOD_CHANNELCONTROL_DSP_FADER)?;
+                class.const_set("DSP_TAIL", libfmod::ffi::FMOD_CHANNELCONTROL_DSP_TAIL)?;
+
+                class.define_method("get_system_object", method!($name::get_system_object, 0))?;
+                class.define_method("stop", method!($name::stop, 0))?;
+                class.define_method("get_paused", method!($name::get_paused, 0))?;
+                class.define_method("set_paused", method!($name::set_paused, 1))?;
+                class.define_method("get_volume", method!($name::get_volume, 0))?;
+                class.define_method("set_volume", method!($name::set_volume, 1))?;
+                class.define_method("get_volume_ramp", method!($name::get_volume_ramp, 0))?;
+                class.define_method("set_volume_ramp", method!($name::set_volume_ramp, 1))?;
+                class.define_method("get_audibility", method!($name::get_audibility, 0))?;
+                class.define_method("get_pitch", method!($name::get_pitch, 0))?;
+                class.define_method("set_pitch", method!($name::set_pitch, 1))?;
+                class.define_method("get_mute", method!($name::get_mute, 0))?;
+                class.define_method("set_mute", method!($name::set_mute, 1))?;
+                class.define_method("get_reverb_properties", method!($name::get_reverb_properties, 1))?;
+                class.define_method("set_reverb_properties", method!($name::set_reverb_properties, 2))?;
+                class.define_method("get_low_pass_gain", method!($name::get_low_pass_gain, 0))?;
+                class.define_method("set_low_pass_gain", method!($name::set_low_pass_gain, 1))?;
+                class.define_method("get_mode", method!($name::get_mode, 0))?;
+                class.define_method("set_mode", method!($name::set_mode, 1))?;
+                class.define_method("is_playing", method!($name::is_playing, 0))?;
+                class.define_method("set_pan", method!($name::set_pan, 1))?;
+                class.define_method("set_mix_levels_output", method!($name::set_mix_levels_output, 8))?;
+                class.define_method("set_mix_levels_input", method!($name::set_mix_levels_input, 1))?;
+                class.define_method("get_mix_matrix", method!($name::get_mix_matrix, 0))?;
+                class.define_method("set_mix_matrix", method!($name::set_mix_matrix, 3))?;
+                class.define_method("get_dsp_clock", method!($name::get_dsp_clock, 0))?;
+                class.define_method("get_delay", method!($name::get_delay, 0))?;
+                class.define_method("set_delay", method!($name::set_delay, 3))?;
+                class.define_method("add_fade_point", method!($name::add_fade_point, 2))?;
+                class.define_method("set_fade_point_ramp", method!($name::set_fade_point_ramp, 2))?;
+                class.define_method("remove_fade_points", method!($name::remove_fade_points, 2))?;
+                class.define_method("get_fade_points", method!($name::get_fade_points, 0))?;
+                class.define_method("get_3d_attributes", method!($name::get_3d_attributes, 0))?;
+                class.define_method("set_3d_attributes", method!($name::set_3d_attributes, 2))?;
+                class.define_method("get_3d_min_max_distance", method!($name::get_3d_min_max_distance, 0))?;
+                class.define_method("set_3d_min_max_distance", method!($name::set_3d_min_max_distance, 2))?;
+                class.define_method("get_3d_cone_settings", method!($name::get_3d_cone_settings, 0))?;
+                class.define_method("set_3d_cone_settings", method!($name::set_3d_cone_settings, 3))?;
+                class.define_method("get_3d_occlusion", method!($name::get_3d_occlusion, 0))?;
+                class.define_method("set_3d_occlusion", method!($name::set_3d_occlusion, 2))?;
+                class.define_method("get_3d_spread", method!($name::get_3d_spread, 0))?;
+                class.define_method("set_3d_spread", method!($name::set_3d_spread, 1))?;
+                class.define_method("get_3d_level", method!($name::get_3d_level, 0))?;
+                class.define_method("set_3d_level", method!($name::set_3d_level, 1))?;
+                class.define_method("get_3d_doppler_level", method!($name::get_3d_doppler_level, 0))?;
+                class.define_method("set_3d_doppler_level", method!($name::set_3d_doppler_level, 1))?;
+                class.define_method("get_num_dsps", method!($name::get_num_dsps, 0))?;
+                class.define_method("get_dsp", method!($name::get_dsp, 1))?;
+                class.define_method("add_dsp", method!($name::add_dsp, 2))?;
+                class.define_method("remove_dsp", method!($name::remove_dsp, 1))?;
+                class.define_method("get_dsp_index", method!($name::get_dsp_index, 1))?;
+                class.define_method("set_dsp_index", method!($name::set_dsp_index, 2))?;
+                class.define_method("set_callback", method!($name::set_callback, 1))?;
+                class.define_method("get_user_data", method!($name::get_user_data, 0))?;
+                class.define_method("set_user_data", method!($name::set_user_data, 1))?;
+
+                Ok(())
+            }
+        }
+    };
+}
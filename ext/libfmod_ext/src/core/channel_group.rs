@@ -0,0 +1,109 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of libfmod.
+//
+// libfmod is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// libfmod is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::core::channel::Channel;
+use crate::err_fmod;
+#[allow(unused_imports)]
+use crate::{
+    bind_fn, channel_control, opaque_struct, opaque_struct_function, opaque_struct_method,
+};
+
+opaque_struct!(ChannelGroup, "Core", "ChannelGroup");
+
+channel_control!(ChannelGroup, "ChannelGroup", ChannelGroup);
+
+impl ChannelGroup {
+    // Groups never end like channels do, so their user data is freed here instead.
+    fn release(&self) -> Result<(), magnus::Error> {
+        use crate::core::channel_control::ChannelControlUserData;
+        use crate::wrap::WrapFMOD;
+
+        let user_data = self.0.get_user_data().map_err(|e| e.wrap_fmod())?;
+        self.0.release().map_err(|e| e.wrap_fmod())?;
+
+        if !user_data.is_null() {
+            let user_data = unsafe { Box::from_raw(user_data as *mut ChannelControlUserData) };
+            crate::callback::UserDataRelease::create(user_data);
+        }
+
+        Ok(())
+    }
+
+    // libfmod wants to hand back a DSPConnection here, which we do not need.
+    fn add_group(
+        &self,
+        group: &ChannelGroup,
+        propagatedspclock: bool,
+    ) -> Result<(), magnus::Error> {
+        unsafe {
+            match libfmod::ffi::FMOD_ChannelGroup_AddGroup(
+                self.0.as_mut_ptr(),
+                group.0.as_mut_ptr(),
+                propagatedspclock as _,
+                std::ptr::null_mut(),
+            ) {
+                libfmod::ffi::FMOD_OK => Ok(()),
+                err => Err(err_fmod!("FMOD_ChannelGroup_AddGroup", err)),
+            }
+        }
+    }
+
+    opaque_struct_method!(get_num_groups, i32;);
+    opaque_struct_method!(get_group, ChannelGroup; (i32));
+    opaque_struct_method!(get_parent_group, ChannelGroup;);
+
+    // Like Sound::getName, FMOD just truncates the name to fit the buffer.
+    fn get_name(&self) -> Result<String, magnus::Error> {
+        unsafe {
+            let mut buffer = vec![0u8; 512];
+
+            match libfmod::ffi::FMOD_ChannelGroup_GetName(
+                self.0.as_mut_ptr(),
+                buffer.as_mut_ptr() as *mut _,
+                buffer.len() as _,
+            ) {
+                libfmod::ffi::FMOD_OK => {
+                    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+                    buffer.truncate(len);
+
+                    Ok(String::from_utf8_lossy(&buffer).into_owned())
+                }
+                err => Err(err_fmod!("FMOD_ChannelGroup_GetName", err)),
+            }
+        }
+    }
+
+    opaque_struct_method!(get_num_channels, i32;);
+    opaque_struct_method!(get_channel, Channel; (i32));
+
+    bind_fn! {
+        ChannelGroup, "ChannelGroup";
+        (release, method, 0),
+        (add_group, method, 2),
+        (get_num_groups, method, 0),
+        (get_group, method, 1),
+        (get_parent_group, method, 0),
+        (get_name, method, 0),
+        (get_num_channels, method, 0),
+        (get_channel, method, 1)
+    }
+}
+
+pub fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    ChannelGroup::bind(module)?;
+    ChannelGroup::bind_channel_control(module)
+}
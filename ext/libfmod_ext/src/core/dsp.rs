@@ -0,0 +1,458 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of libfmod.
+//
+// libfmod is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// libfmod is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use magnus::{value::BoxValue, RString, RStruct};
+
+use crate::callback::{DspReadCallback, UserDataRelease};
+use crate::core::system::System;
+use crate::enums::{DspConnectionType, DspType};
+use crate::err_fmod;
+#[allow(unused_imports)]
+use crate::{bind_fn, opaque_struct, opaque_struct_function, opaque_struct_method};
+
+opaque_struct!(Dsp, "Core", "DSP");
+
+impl Dsp {
+    opaque_struct_method!(release, (););
+    opaque_struct_method!(get_system_object, System;);
+    opaque_struct_method!(get_type, DspType;);
+
+    // Returns the name, version, channels, config width and config height of this unit.
+    fn get_info(&self) -> Result<(String, u32, i32, i32, i32), magnus::Error> {
+        unsafe {
+            let mut name = [0u8; 32];
+            let mut version = 0;
+            let mut channels = 0;
+            let mut configwidth = 0;
+            let mut configheight = 0;
+
+            match libfmod::ffi::FMOD_DSP_GetInfo(
+                self.0.as_mut_ptr(),
+                name.as_mut_ptr() as *mut _,
+                &mut version,
+                &mut channels,
+                &mut configwidth,
+                &mut configheight,
+            ) {
+                libfmod::ffi::FMOD_OK => {
+                    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+
+                    Ok((
+                        String::from_utf8_lossy(&name[..len]).into_owned(),
+                        version,
+                        channels,
+                        configwidth,
+                        configheight,
+                    ))
+                }
+                err => Err(err_fmod!("FMOD_DSP_GetInfo", err)),
+            }
+        }
+    }
+
+    opaque_struct_method!(add_input, DspConnection; (&Dsp), (&DspConnectionType));
+
+    // Either argument can be nil. If both are nil then every input is disconnected.
+    fn disconnect_from(
+        &self,
+        target: Option<&Dsp>,
+        connection: Option<&DspConnection>,
+    ) -> Result<(), magnus::Error> {
+        unsafe {
+            match libfmod::ffi::FMOD_DSP_DisconnectFrom(
+                self.0.as_mut_ptr(),
+                target.map_or(std::ptr::null_mut(), |d| d.0.as_mut_ptr()),
+                connection.map_or(std::ptr::null_mut(), |c| c.0.as_mut_ptr()),
+            ) {
+                libfmod::ffi::FMOD_OK => Ok(()),
+                err => Err(err_fmod!("FMOD_DSP_DisconnectFrom", err)),
+            }
+        }
+    }
+
+    opaque_struct_method!(disconnect_all, (); (bool), (bool));
+    opaque_struct_method!(get_num_inputs, i32;);
+    opaque_struct_method!(get_num_outputs, i32;);
+    opaque_struct_method!(get_input, (Dsp, DspConnection); (i32));
+    opaque_struct_method!(get_output, (Dsp, DspConnection); (i32));
+    opaque_struct_method!(get_active, bool;);
+    opaque_struct_method!(set_active, (); (bool));
+    opaque_struct_method!(get_bypass, bool;);
+    opaque_struct_method!(set_bypass, (); (bool));
+    opaque_struct_method!(get_wet_dry_mix, (f32, f32, f32););
+    opaque_struct_method!(set_wet_dry_mix, (); (f32), (f32), (f32));
+    opaque_struct_method!(get_idle, bool;);
+    opaque_struct_method!(reset, (););
+    opaque_struct_method!(get_num_parameters, i32;);
+    opaque_struct_method!(set_parameter_float, (); (i32), (f32));
+    opaque_struct_method!(set_parameter_int, (); (i32), (i32));
+    opaque_struct_method!(set_parameter_bool, (); (i32), (bool));
+
+    // libfmod also wants to hand us a value string for these, which we do not care about.
+    fn get_parameter_float(&self, index: i32) -> Result<f32, magnus::Error> {
+        unsafe {
+            let mut value = 0.0;
+
+            match libfmod::ffi::FMOD_DSP_GetParameterFloat(
+                self.0.as_mut_ptr(),
+                index,
+                &mut value,
+                std::ptr::null_mut(),
+                0,
+            ) {
+                libfmod::ffi::FMOD_OK => Ok(value),
+                err => Err(err_fmod!("FMOD_DSP_GetParameterFloat", err)),
+            }
+        }
+    }
+
+    fn get_parameter_int(&self, index: i32) -> Result<i32, magnus::Error> {
+        unsafe {
+            let mut value = 0;
+
+            match libfmod::ffi::FMOD_DSP_GetParameterInt(
+                self.0.as_mut_ptr(),
+                index,
+                &mut value,
+                std::ptr::null_mut(),
+                0,
+            ) {
+                libfmod::ffi::FMOD_OK => Ok(value),
+                err => Err(err_fmod!("FMOD_DSP_GetParameterInt", err)),
+            }
+        }
+    }
+
+    fn get_parameter_bool(&self, index: i32) -> Result<bool, magnus::Error> {
+        unsafe {
+            let mut value = 0;
+
+            match libfmod::ffi::FMOD_DSP_GetParameterBool(
+                self.0.as_mut_ptr(),
+                index,
+                &mut value,
+                std::ptr::null_mut(),
+                0,
+            ) {
+                libfmod::ffi::FMOD_OK => Ok(value != 0),
+                err => Err(err_fmod!("FMOD_DSP_GetParameterBool", err)),
+            }
+        }
+    }
+
+    // Data parameters are opaque blobs (FFT spectrums, convolution IRs, etc), so they are passed around as binary strings.
+    fn get_parameter_data(&self, index: i32) -> Result<RString, magnus::Error> {
+        unsafe {
+            let mut data = std::ptr::null_mut();
+            let mut length = 0;
+
+            match libfmod::ffi::FMOD_DSP_GetParameterData(
+                self.0.as_mut_ptr(),
+                index,
+                &mut data,
+                &mut length,
+                std::ptr::null_mut(),
+                0,
+            ) {
+                libfmod::ffi::FMOD_OK if data.is_null() => Ok(RString::buf_new(0)),
+                libfmod::ffi::FMOD_OK => Ok(RString::from_slice(std::slice::from_raw_parts(
+                    data as *const u8,
+                    length as _,
+                ))),
+                err => Err(err_fmod!("FMOD_DSP_GetParameterData", err)),
+            }
+        }
+    }
+
+    fn set_parameter_data(&self, index: i32, data: RString) -> Result<(), magnus::Error> {
+        unsafe {
+            // FMOD copies the data, so we do not need to keep this around.
+            let mut data = data.as_slice().to_vec();
+
+            match libfmod::ffi::FMOD_DSP_SetParameterData(
+                self.0.as_mut_ptr(),
+                index,
+                data.as_mut_ptr() as *mut _,
+                data.len() as _,
+            ) {
+                libfmod::ffi::FMOD_OK => Ok(()),
+                err => Err(err_fmod!("FMOD_DSP_SetParameterData", err)),
+            }
+        }
+    }
+
+    opaque_struct_method!(get_metering_enabled, (bool, bool););
+    opaque_struct_method!(set_metering_enabled, (); (bool), (bool));
+    opaque_struct_method!(get_metering_info, (RStruct, RStruct););
+
+    bind_fn! {
+        Dsp, "DSP";
+        (release, method, 0),
+        (get_system_object, method, 0),
+        (get_type, method, 0),
+        (get_info, method, 0),
+        (add_input, method, 2),
+        (disconnect_from, method, 2),
+        (disconnect_all, method, 2),
+        (get_num_inputs, method, 0),
+        (get_num_outputs, method, 0),
+        (get_input, method, 1),
+        (get_output, method, 1),
+        (get_active, method, 0),
+        (set_active, method, 1),
+        (get_bypass, method, 0),
+        (set_bypass, method, 1),
+        (get_wet_dry_mix, method, 0),
+        (set_wet_dry_mix, method, 3),
+        (get_idle, method, 0),
+        (reset, method, 0),
+        (get_num_parameters, method, 0),
+        (set_parameter_float, method, 2),
+        (set_parameter_int, method, 2),
+        (set_parameter_bool, method, 2),
+        (set_parameter_data, method, 2),
+        (get_parameter_float, method, 1),
+        (get_parameter_int, method, 1),
+        (get_parameter_bool, method, 1),
+        (get_parameter_data, method, 1),
+        (get_metering_enabled, method, 0),
+        (set_metering_enabled, method, 2),
+        (get_metering_info, method, 0)
+    }
+}
+
+opaque_struct!(DspConnection, "Core", "DSPConnection");
+
+impl DspConnection {
+    opaque_struct_method!(get_input, Dsp;);
+    opaque_struct_method!(get_output, Dsp;);
+    opaque_struct_method!(get_mix, f32;);
+    opaque_struct_method!(set_mix, (); (f32));
+    opaque_struct_method!(get_type, DspConnectionType;);
+
+    bind_fn! {
+        DspConnection, "DSPConnection";
+        (get_input, method, 0),
+        (get_output, method, 0),
+        (get_mix, method, 0),
+        (set_mix, method, 1),
+        (get_type, method, 0)
+    }
+}
+
+// How many read callbacks a single custom DSP may have queued before we start dropping buffers.
+// Ruby can easily fall behind the mixer, and there is no point in queueing stale audio forever.
+const MAX_PENDING_READS: usize = 8;
+
+// Precompiled processors that run on the mixer thread, before the buffer is handed to Ruby.
+#[derive(Clone, Copy)]
+pub enum DspProcessor {
+    Passthrough,
+    Silence,
+}
+
+impl DspProcessor {
+    fn process(self, input: &[f32], output: &mut [f32], inchannels: usize, outchannels: usize) {
+        match self {
+            // FMOD can hand us no channels at all (e.g. an idle input), and there is nothing to copy then.
+            Self::Passthrough if inchannels == 0 || outchannels == 0 => output.fill(0.0),
+            Self::Passthrough => {
+                for (in_frame, out_frame) in input
+                    .chunks_exact(inchannels)
+                    .zip(output.chunks_exact_mut(outchannels))
+                {
+                    for (c, sample) in out_frame.iter_mut().enumerate() {
+                        *sample = in_frame.get(c).copied().unwrap_or_default();
+                    }
+                }
+            }
+            Self::Silence => output.fill(0.0),
+        }
+    }
+}
+
+// Shared between the mixer thread and any queued Ruby callbacks.
+pub struct DspReadState {
+    pub callback: Option<BoxValue<magnus::Value>>,
+    pub pending: AtomicUsize,
+    processor: DspProcessor,
+}
+
+// Stored as the description's user data. FMOD gives us this back in every plugin callback.
+type DspStateUserData = Arc<DspReadState>;
+
+unsafe fn get_state_user_data(
+    dsp_state: *mut libfmod::ffi::FMOD_DSP_STATE,
+) -> Option<&'static DspStateUserData> {
+    let mut userdata = std::ptr::null_mut();
+    let getuserdata = (*(*dsp_state).functions).getuserdata?;
+    getuserdata(dsp_state, &mut userdata);
+
+    (userdata as *const DspStateUserData).as_ref()
+}
+
+unsafe extern "C" fn dsp_read(
+    dsp_state: *mut libfmod::ffi::FMOD_DSP_STATE,
+    inbuffer: *mut f32,
+    outbuffer: *mut f32,
+    length: std::ffi::c_uint,
+    inchannels: std::ffi::c_int,
+    outchannels: *mut std::ffi::c_int,
+) -> libfmod::ffi::FMOD_RESULT {
+    let Some(state) = get_state_user_data(dsp_state) else {
+        return libfmod::ffi::FMOD_OK;
+    };
+
+    let inchannels = inchannels as usize;
+    let outchannels = *outchannels as usize;
+    let input = std::slice::from_raw_parts(inbuffer, length as usize * inchannels);
+    let output = std::slice::from_raw_parts_mut(outbuffer, length as usize * outchannels);
+
+    state
+        .processor
+        .process(input, output, inchannels, outchannels);
+
+    // We never wait on Ruby here, this is the mixer thread!
+    if state.callback.is_some() && state.pending.load(Ordering::Acquire) < MAX_PENDING_READS {
+        state.pending.fetch_add(1, Ordering::AcqRel);
+
+        DspReadCallback::create(
+            libfmod::Dsp::from((*dsp_state).instance as *mut _),
+            input.to_vec(),
+            inchannels as i32,
+            state.clone(),
+        );
+    }
+
+    libfmod::ffi::FMOD_OK
+}
+
+unsafe extern "C" fn dsp_release(
+    dsp_state: *mut libfmod::ffi::FMOD_DSP_STATE,
+) -> libfmod::ffi::FMOD_RESULT {
+    // This is called from the mixer thread, so the read callback has to be unrooted elsewhere.
+    if let Some(state) = get_state_user_data(dsp_state) {
+        UserDataRelease::create(Box::from_raw(state as *const _ as *mut DspStateUserData));
+    }
+
+    libfmod::ffi::FMOD_OK
+}
+
+struct DspDescriptionInner {
+    name: String,
+    processor: DspProcessor,
+    read: Option<BoxValue<magnus::Value>>,
+}
+
+// A builder for custom DSP units, turned into a DSP by System#create_dsp.
+#[magnus::wrap(class = "FMOD::Core::DSPDescription", free_immediatly, size)]
+pub(crate) struct DspDescription(RefCell<DspDescriptionInner>);
+
+// The description is only ever touched from Ruby threads.
+unsafe impl Send for DspDescription {}
+
+impl DspDescription {
+    fn new(name: String) -> Result<Self, magnus::Error> {
+        // FMOD only has room for 31 characters and a nul.
+        if name.len() > 31 || name.contains('\0') {
+            return Err(magnus::Error::new(
+                magnus::exception::arg_error(),
+                "dsp names must be at most 31 bytes and not contain nul",
+            ));
+        }
+
+        Ok(Self(RefCell::new(DspDescriptionInner {
+            name,
+            processor: DspProcessor::Passthrough,
+            read: None,
+        })))
+    }
+
+    fn get_name(&self) -> String {
+        self.0.borrow().name.clone()
+    }
+
+    fn set_processor(&self, processor: String) -> Result<(), magnus::Error> {
+        self.0.borrow_mut().processor = match processor.as_str() {
+            "passthrough" => DspProcessor::Passthrough,
+            "silence" => DspProcessor::Silence,
+            _ => {
+                return Err(magnus::Error::new(
+                    magnus::exception::arg_error(),
+                    format!("unknown dsp processor {processor}"),
+                ))
+            }
+        };
+
+        Ok(())
+    }
+
+    // The callback is called with the DSP, the interleaved input buffer and the input channel count.
+    fn set_read_callback(&self, callback: Option<magnus::Value>) {
+        self.0.borrow_mut().read = callback.map(BoxValue::new);
+    }
+
+    pub(crate) fn to_fmod(&self) -> libfmod::ffi::FMOD_DSP_DESCRIPTION {
+        let inner = self.0.borrow();
+
+        let mut description: libfmod::ffi::FMOD_DSP_DESCRIPTION = unsafe { std::mem::zeroed() };
+        description.pluginsdkversion = libfmod::ffi::FMOD_PLUGIN_SDK_VERSION;
+        for (c, b) in description.name.iter_mut().zip(inner.name.bytes()) {
+            *c = b as _;
+        }
+        description.version = 0x00010000;
+        description.numinputbuffers = 1;
+        description.numoutputbuffers = 1;
+        description.read = Some(dsp_read);
+        description.release = Some(dsp_release);
+
+        // Freed by dsp_release.
+        let state: Box<DspStateUserData> = Box::new(Arc::new(DspReadState {
+            callback: inner.read.as_ref().map(|c| BoxValue::new(**c)),
+            pending: AtomicUsize::new(0),
+            processor: inner.processor,
+        }));
+        description.userdata = Box::into_raw(state) as *mut _;
+
+        description
+    }
+
+    fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
+        use magnus::{function, method, Module, Object};
+
+        let class = module.define_class("DSPDescription", Default::default())?;
+        class.define_singleton_method("new", function!(DspDescription::new, 1))?;
+        class.define_method("get_name", method!(DspDescription::get_name, 0))?;
+        class.define_method("set_processor", method!(DspDescription::set_processor, 1))?;
+        class.define_method(
+            "set_read_callback",
+            method!(DspDescription::set_read_callback, 1),
+        )?;
+
+        Ok(())
+    }
+}
+
+pub fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    Dsp::bind(module)?;
+    DspConnection::bind(module)?;
+    DspDescription::bind(module)
+}
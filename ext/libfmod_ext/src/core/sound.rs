@@ -0,0 +1,158 @@
+// Copyright (C) 2023 Lily Lyons
+//
+// This file is part of libfmod.
+//
+// libfmod is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// libfmod is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::enums::{OpenState, SoundFormat, SoundType};
+use crate::err_fmod;
+#[allow(unused_imports)]
+use crate::{bind_fn, opaque_struct, opaque_struct_function, opaque_struct_method};
+
+opaque_struct!(Sound, "Core", "Sound");
+
+impl Sound {
+    // FMOD_Sound_GetName does not tell us how long the name is, it just truncates it.
+    fn get_name(&self) -> Result<String, magnus::Error> {
+        unsafe {
+            let mut buffer = vec![0u8; 512];
+
+            match libfmod::ffi::FMOD_Sound_GetName(
+                self.0.as_mut_ptr(),
+                buffer.as_mut_ptr() as *mut _,
+                buffer.len() as _,
+            ) {
+                libfmod::ffi::FMOD_OK => {
+                    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+                    buffer.truncate(len);
+
+                    Ok(String::from_utf8_lossy(&buffer).into_owned())
+                }
+                err => Err(err_fmod!("FMOD_Sound_GetName", err)),
+            }
+        }
+    }
+
+    opaque_struct_method!(release, (););
+    opaque_struct_method!(get_length, u32; (u32));
+    opaque_struct_method!(get_format, (SoundType, SoundFormat, i32, i32););
+    opaque_struct_method!(get_defaults, (f32, i32););
+    opaque_struct_method!(set_defaults, (); (f32), (i32));
+    opaque_struct_method!(get_loop_points, (u32, u32); (u32), (u32));
+    opaque_struct_method!(set_loop_points, (); (u32), (u32), (u32), (u32));
+    opaque_struct_method!(get_loop_count, i32;);
+    opaque_struct_method!(set_loop_count, (); (i32));
+    opaque_struct_method!(get_mode, u32;);
+    opaque_struct_method!(set_mode, (); (u32));
+    opaque_struct_method!(get_open_state, (OpenState, u32, bool, bool););
+    opaque_struct_method!(get_num_sub_sounds, i32;);
+    opaque_struct_method!(get_sub_sound, Sound; (i32));
+    opaque_struct_method!(get_sub_sound_parent, Sound;);
+
+    bind_fn! {
+        Sound, "Sound";
+        (get_name, method, 0),
+        (release, method, 0),
+        (get_length, method, 1),
+        (get_format, method, 0),
+        (get_defaults, method, 0),
+        (set_defaults, method, 2),
+        (get_loop_points, method, 2),
+        (set_loop_points, method, 4),
+        (get_loop_count, method, 0),
+        (set_loop_count, method, 1),
+        (get_mode, method, 0),
+        (set_mode, method, 1),
+        (get_open_state, method, 0),
+        (get_num_sub_sounds, method, 0),
+        (get_sub_sound, method, 1),
+        (get_sub_sound_parent, method, 0)
+    }
+}
+
+fn bind_mode(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    let mode = module.define_module("Mode")?;
+
+    mode.const_set("DEFAULT", libfmod::ffi::FMOD_DEFAULT)?;
+    mode.const_set("LOOP_OFF", libfmod::ffi::FMOD_LOOP_OFF)?;
+    mode.const_set("LOOP_NORMAL", libfmod::ffi::FMOD_LOOP_NORMAL)?;
+    mode.const_set("LOOP_BIDI", libfmod::ffi::FMOD_LOOP_BIDI)?;
+    mode.const_set("TWO_D", libfmod::ffi::FMOD_2D)?;
+    mode.const_set("THREE_D", libfmod::ffi::FMOD_3D)?;
+    mode.const_set("CREATESTREAM", libfmod::ffi::FMOD_CREATESTREAM)?;
+    mode.const_set("CREATESAMPLE", libfmod::ffi::FMOD_CREATESAMPLE)?;
+    mode.const_set(
+        "CREATECOMPRESSEDSAMPLE",
+        libfmod::ffi::FMOD_CREATECOMPRESSEDSAMPLE,
+    )?;
+    mode.const_set("OPENUSER", libfmod::ffi::FMOD_OPENUSER)?;
+    mode.const_set("OPENMEMORY", libfmod::ffi::FMOD_OPENMEMORY)?;
+    mode.const_set("OPENMEMORY_POINT", libfmod::ffi::FMOD_OPENMEMORY_POINT)?;
+    mode.const_set("OPENRAW", libfmod::ffi::FMOD_OPENRAW)?;
+    mode.const_set("OPENONLY", libfmod::ffi::FMOD_OPENONLY)?;
+    mode.const_set("ACCURATETIME", libfmod::ffi::FMOD_ACCURATETIME)?;
+    mode.const_set("MPEGSEARCH", libfmod::ffi::FMOD_MPEGSEARCH)?;
+    mode.const_set("NONBLOCKING", libfmod::ffi::FMOD_NONBLOCKING)?;
+    mode.const_set("UNIQUE", libfmod::ffi::FMOD_UNIQUE)?;
+    mode.const_set("THREE_D_HEADRELATIVE", libfmod::ffi::FMOD_3D_HEADRELATIVE)?;
+    mode.const_set("THREE_D_WORLDRELATIVE", libfmod::ffi::FMOD_3D_WORLDRELATIVE)?;
+    mode.const_set(
+        "THREE_D_INVERSEROLLOFF",
+        libfmod::ffi::FMOD_3D_INVERSEROLLOFF,
+    )?;
+    mode.const_set("THREE_D_LINEARROLLOFF", libfmod::ffi::FMOD_3D_LINEARROLLOFF)?;
+    mode.const_set(
+        "THREE_D_LINEARSQUAREROLLOFF",
+        libfmod::ffi::FMOD_3D_LINEARSQUAREROLLOFF,
+    )?;
+    mode.const_set(
+        "THREE_D_INVERSETAPEREDROLLOFF",
+        libfmod::ffi::FMOD_3D_INVERSETAPEREDROLLOFF,
+    )?;
+    mode.const_set("THREE_D_CUSTOMROLLOFF", libfmod::ffi::FMOD_3D_CUSTOMROLLOFF)?;
+    mode.const_set(
+        "THREE_D_IGNOREGEOMETRY",
+        libfmod::ffi::FMOD_3D_IGNOREGEOMETRY,
+    )?;
+    mode.const_set("IGNORETAGS", libfmod::ffi::FMOD_IGNORETAGS)?;
+    mode.const_set("LOWMEM", libfmod::ffi::FMOD_LOWMEM)?;
+    mode.const_set(
+        "VIRTUAL_PLAYFROMSTART",
+        libfmod::ffi::FMOD_VIRTUAL_PLAYFROMSTART,
+    )?;
+
+    Ok(())
+}
+
+fn bind_time_unit(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    let time_unit = module.define_module("TimeUnit")?;
+
+    time_unit.const_set("MS", libfmod::ffi::FMOD_TIMEUNIT_MS)?;
+    time_unit.const_set("PCM", libfmod::ffi::FMOD_TIMEUNIT_PCM)?;
+    time_unit.const_set("PCMBYTES", libfmod::ffi::FMOD_TIMEUNIT_PCMBYTES)?;
+    time_unit.const_set("RAWBYTES", libfmod::ffi::FMOD_TIMEUNIT_RAWBYTES)?;
+    time_unit.const_set("PCMFRACTION", libfmod::ffi::FMOD_TIMEUNIT_PCMFRACTION)?;
+    time_unit.const_set("MODORDER", libfmod::ffi::FMOD_TIMEUNIT_MODORDER)?;
+    time_unit.const_set("MODROW", libfmod::ffi::FMOD_TIMEUNIT_MODROW)?;
+    time_unit.const_set("MODPATTERN", libfmod::ffi::FMOD_TIMEUNIT_MODPATTERN)?;
+
+    Ok(())
+}
+
+pub fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    bind_mode(module)?;
+    bind_time_unit(module)?;
+
+    Sound::bind(module)
+}
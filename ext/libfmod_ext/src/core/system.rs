@@ -15,10 +15,28 @@
 // You should have received a copy of the GNU General Public License
 // along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
 
+use magnus::{RString, RStruct};
+
+use crate::core::channel::Channel;
+use crate::core::channel_group::ChannelGroup;
+use crate::core::dsp::{Dsp, DspDescription};
+use crate::core::sound::Sound;
+use crate::enums::DspType;
+use crate::err_fmod;
+use crate::thread::without_gvl_no_ubf;
+use crate::transparent_struct::CreateSoundExInfo;
 use crate::{bind_fn, opaque_struct, opaque_struct_function, opaque_struct_method};
 
 opaque_struct!(System, "Core", "System");
 
+type CreateSoundFn = unsafe extern "C" fn(
+    *mut libfmod::ffi::FMOD_SYSTEM,
+    *const std::ffi::c_char,
+    libfmod::ffi::FMOD_MODE,
+    *mut libfmod::ffi::FMOD_CREATESOUNDEXINFO,
+    *mut *mut libfmod::ffi::FMOD_SOUND,
+) -> libfmod::ffi::FMOD_RESULT;
+
 impl System {
     opaque_struct_function!(System, create, Self;);
 
@@ -30,11 +48,261 @@ impl System {
 
     opaque_struct_method!(close, (););
 
+    // A released system can't use its file system handler anymore, so another one may have it.
+    fn release(&self) -> Result<(), magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let system = self.0.as_mut_ptr();
+        self.0.release().wrap_fmod()?;
+        crate::file::set_file_system(system, None);
+
+        Ok(())
+    }
+
+    // Same as Studio::System#update, this can call callbacks synchronously so we have to release the GVL.
+    fn update(&self) -> Result<(), magnus::Error> {
+        crate::callback::dispatch_queued();
+
+        unsafe {
+            use crate::wrap::WrapFMOD;
+
+            let system = self.0;
+            without_gvl_no_ubf(|| system.update()).map_err(|e| e.wrap_fmod())?;
+        }
+
+        crate::callback::dispatch_queued();
+        crate::callback::raise_pending_error()
+    }
+
+    // createSound and createStream are identical apart from the FMOD_CREATESTREAM flag, so they share this.
+    // FMOD_OPENMEMORY_POINT is turned into FMOD_OPENMEMORY, as FMOD would keep pointing into the string for as long as
+    // the sound lives. The data is copied instead, like it is for FMOD_OPENMEMORY.
+    // name_or_data is ignored (and can be nil) with FMOD_OPENUSER, where exinfo describes the sound instead.
+    fn create_sound_with(
+        &self,
+        create: CreateSoundFn,
+        fn_name: &'static str,
+        name_or_data: Option<RString>,
+        mode: std::ffi::c_uint,
+        exinfo: Option<RStruct>,
+    ) -> Result<Sound, magnus::Error> {
+        use crate::wrap::TryUnwrapFMOD;
+        use crate::wrap::WrapFMOD;
+
+        let mut mode = mode;
+        if mode & libfmod::ffi::FMOD_OPENMEMORY_POINT != 0 {
+            mode = (mode & !libfmod::ffi::FMOD_OPENMEMORY_POINT) | libfmod::ffi::FMOD_OPENMEMORY;
+        }
+
+        let mut exinfo: Option<CreateSoundExInfo> = exinfo.try_unwrap_fmod()?;
+
+        let name_or_data = match name_or_data {
+            _ if mode & libfmod::ffi::FMOD_OPENUSER != 0 => None,
+            None => {
+                return Err(magnus::Error::new(
+                    magnus::exception::type_error(),
+                    "name_or_data can only be nil with FMOD_OPENUSER",
+                ))
+            }
+            // With FMOD_OPENMEMORY name_or_data is raw data and not a file name.
+            // We copy it out of the Ruby string as we're going to be running without the GVL.
+            Some(name_or_data) if mode & libfmod::ffi::FMOD_OPENMEMORY != 0 => {
+                let data = unsafe { name_or_data.as_slice() };
+
+                // FMOD needs to know how long the data is.
+                // A SoundInfo for a sound in a bank gives where in the bank it is, and only that much is copied.
+                let exinfo = exinfo.get_or_insert_with(CreateSoundExInfo::default);
+                let offset = exinfo.info.fileoffset as usize;
+                let length = match exinfo.info.length {
+                    0 => data.len().saturating_sub(offset),
+                    length => length as usize,
+                };
+                let data = offset
+                    .checked_add(length)
+                    .and_then(|end| data.get(offset..end))
+                    .ok_or_else(|| {
+                        magnus::Error::new(
+                            magnus::exception::index_error(),
+                            format!(
+                                "{length} bytes at offset {offset} are out of range for {} bytes",
+                                data.len()
+                            ),
+                        )
+                    })?
+                    .to_vec();
+
+                exinfo.info.fileoffset = 0;
+                exinfo.info.length = data.len() as _;
+
+                Some(data)
+            }
+            Some(name_or_data) => Some(
+                std::ffi::CString::new(name_or_data.to_string()?)
+                    .map_err(|e| libfmod::Error::StringNul(e).wrap_fmod())?
+                    .into_bytes_with_nul(),
+            ),
+        };
+
+        // Loading a sound can block for a while (and later on may call into Ruby for file callbacks).
+        unsafe {
+            let system = self.0.as_mut_ptr();
+            let exinfo = exinfo
+                .as_mut()
+                .map_or(std::ptr::null_mut(), |e| &mut e.info as *mut _);
+
+            without_gvl_no_ubf(|| {
+                let mut sound = std::ptr::null_mut();
+
+                match create(
+                    system,
+                    name_or_data
+                        .as_ref()
+                        .map_or(std::ptr::null(), |n| n.as_ptr() as *const _),
+                    mode,
+                    exinfo,
+                    &mut sound,
+                ) {
+                    libfmod::ffi::FMOD_OK => Ok(libfmod::Sound::from(sound).wrap_fmod()),
+                    error => Err(err_fmod!(fn_name, error)),
+                }
+            })
+        }
+    }
+
+    fn create_sound(
+        &self,
+        name_or_data: Option<RString>,
+        mode: std::ffi::c_uint,
+        exinfo: Option<RStruct>,
+    ) -> Result<Sound, magnus::Error> {
+        self.create_sound_with(
+            libfmod::ffi::FMOD_System_CreateSound,
+            "FMOD_System_CreateSound",
+            name_or_data,
+            mode,
+            exinfo,
+        )
+    }
+
+    fn create_stream(
+        &self,
+        name_or_data: Option<RString>,
+        mode: std::ffi::c_uint,
+        exinfo: Option<RStruct>,
+    ) -> Result<Sound, magnus::Error> {
+        self.create_sound_with(
+            libfmod::ffi::FMOD_System_CreateStream,
+            "FMOD_System_CreateStream",
+            name_or_data,
+            mode,
+            exinfo,
+        )
+    }
+
+    // A nil channel group plays the sound on the master channel group.
+    opaque_struct_method!(play_sound, Channel; (&Sound), (Option<&ChannelGroup>), (bool));
+    opaque_struct_method!(create_channel_group, ChannelGroup; (String: ref));
+    opaque_struct_method!(get_master_channel_group, ChannelGroup;);
+    opaque_struct_method!(create_dsp_by_type, Dsp; (&DspType));
+    fn create_dsp(&self, description: &DspDescription) -> Result<Dsp, magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let description = description.to_fmod();
+
+        unsafe {
+            let mut dsp = std::ptr::null_mut();
+
+            match libfmod::ffi::FMOD_System_CreateDSP(self.0.as_mut_ptr(), &description, &mut dsp) {
+                libfmod::ffi::FMOD_OK => Ok(libfmod::Dsp::from(dsp).wrap_fmod()),
+                error => {
+                    // FMOD never created the unit, so dsp_release will not free the user data.
+                    drop(Box::from_raw(
+                        description.userdata as *mut std::sync::Arc<crate::core::dsp::DspReadState>,
+                    ));
+
+                    Err(err_fmod!("FMOD_System_CreateDSP", error))
+                }
+            }
+        }
+    }
+
+    opaque_struct_method!(play_dsp, Channel; (&Dsp), (Option<&ChannelGroup>), (bool));
+
+    // Reads every file FMOD opens through handler.open(name), which should return an IO (or nil if there is no such file).
+    // With async, FMOD uses its async read callbacks instead. They still read through Ruby, just on FMOD's file thread.
+    // A nil handler goes back to FMOD's own file system.
+    fn set_file_system(
+        &self,
+        handler: Option<magnus::Value>,
+        block_align: i32,
+        async_: bool,
+    ) -> Result<(), magnus::Error> {
+        use crate::file::*;
+
+        if handler.is_some() {
+            check_file_system(self.0.as_mut_ptr())?;
+        }
+
+        unsafe {
+            let result = match handler {
+                Some(_) if async_ => libfmod::ffi::FMOD_System_SetFileSystem(
+                    self.0.as_mut_ptr(),
+                    Some(file_system_open),
+                    Some(file_close),
+                    None,
+                    None,
+                    Some(file_async_read),
+                    Some(file_async_cancel),
+                    block_align,
+                ),
+                Some(_) => libfmod::ffi::FMOD_System_SetFileSystem(
+                    self.0.as_mut_ptr(),
+                    Some(file_system_open),
+                    Some(file_close),
+                    Some(file_read),
+                    Some(file_seek),
+                    None,
+                    None,
+                    block_align,
+                ),
+                None => libfmod::ffi::FMOD_System_SetFileSystem(
+                    self.0.as_mut_ptr(),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    block_align,
+                ),
+            };
+
+            match result {
+                libfmod::ffi::FMOD_OK => {
+                    set_file_system(self.0.as_mut_ptr(), handler);
+                    Ok(())
+                }
+                error => Err(err_fmod!("FMOD_System_SetFileSystem", error)),
+            }
+        }
+    }
+
     bind_fn!(
         System, "System";
         (create, singleton_method, 0),
         (init, method, 2),
-        (close, method, 0)
+        (close, method, 0),
+        (release, method, 0),
+        (update, method, 0),
+        (create_sound, method, 3),
+        (create_stream, method, 3),
+        (play_sound, method, 3),
+        (create_channel_group, method, 1),
+        (get_master_channel_group, method, 0),
+        (create_dsp, method, 1),
+        (create_dsp_by_type, method, 1),
+        (play_dsp, method, 3),
+        (set_file_system, method, 3)
     );
 }
 
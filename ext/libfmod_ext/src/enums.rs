@@ -171,8 +171,494 @@ bindable_enum!(
     FMOD_STUDIO_INSTANCETYPE_BANK
 );
 
-pub fn bind_enums(module: impl magnus::Module) -> Result<(), magnus::Error> {
+bindable_enum!(
+    SoundType,
+    FMOD_SOUND_TYPE,
+    force_int FMOD_SOUND_TYPE_FORCEINT;
+    Unknown,
+    FMOD_SOUND_TYPE_UNKNOWN,
+    Aiff,
+    FMOD_SOUND_TYPE_AIFF,
+    Asf,
+    FMOD_SOUND_TYPE_ASF,
+    Dls,
+    FMOD_SOUND_TYPE_DLS,
+    Flac,
+    FMOD_SOUND_TYPE_FLAC,
+    Fsb,
+    FMOD_SOUND_TYPE_FSB,
+    It,
+    FMOD_SOUND_TYPE_IT,
+    Midi,
+    FMOD_SOUND_TYPE_MIDI,
+    Mod,
+    FMOD_SOUND_TYPE_MOD,
+    Mpeg,
+    FMOD_SOUND_TYPE_MPEG,
+    OggVorbis,
+    FMOD_SOUND_TYPE_OGGVORBIS,
+    Playlist,
+    FMOD_SOUND_TYPE_PLAYLIST,
+    Raw,
+    FMOD_SOUND_TYPE_RAW,
+    S3m,
+    FMOD_SOUND_TYPE_S3M,
+    User,
+    FMOD_SOUND_TYPE_USER,
+    Wav,
+    FMOD_SOUND_TYPE_WAV,
+    Xm,
+    FMOD_SOUND_TYPE_XM,
+    Xma,
+    FMOD_SOUND_TYPE_XMA,
+    AudioQueue,
+    FMOD_SOUND_TYPE_AUDIOQUEUE,
+    At9,
+    FMOD_SOUND_TYPE_AT9,
+    Vorbis,
+    FMOD_SOUND_TYPE_VORBIS,
+    MediaFoundation,
+    FMOD_SOUND_TYPE_MEDIA_FOUNDATION,
+    MediaCodec,
+    FMOD_SOUND_TYPE_MEDIACODEC,
+    Fadpcm,
+    FMOD_SOUND_TYPE_FADPCM,
+    Opus,
+    FMOD_SOUND_TYPE_OPUS,
+    Max,
+    FMOD_SOUND_TYPE_MAX
+);
+
+bindable_enum!(
+    SoundFormat,
+    FMOD_SOUND_FORMAT,
+    force_int FMOD_SOUND_FORMAT_FORCEINT;
+    None,
+    FMOD_SOUND_FORMAT_NONE,
+    Pcm8,
+    FMOD_SOUND_FORMAT_PCM8,
+    Pcm16,
+    FMOD_SOUND_FORMAT_PCM16,
+    Pcm24,
+    FMOD_SOUND_FORMAT_PCM24,
+    Pcm32,
+    FMOD_SOUND_FORMAT_PCM32,
+    PcmFloat,
+    FMOD_SOUND_FORMAT_PCMFLOAT,
+    Bitstream,
+    FMOD_SOUND_FORMAT_BITSTREAM,
+    Max,
+    FMOD_SOUND_FORMAT_MAX
+);
+
+bindable_enum!(
+    OpenState,
+    FMOD_OPENSTATE,
+    force_int FMOD_OPENSTATE_FORCEINT;
+    Ready,
+    FMOD_OPENSTATE_READY,
+    Loading,
+    FMOD_OPENSTATE_LOADING,
+    Error,
+    FMOD_OPENSTATE_ERROR,
+    Connecting,
+    FMOD_OPENSTATE_CONNECTING,
+    Buffering,
+    FMOD_OPENSTATE_BUFFERING,
+    Seeking,
+    FMOD_OPENSTATE_SEEKING,
+    Playing,
+    FMOD_OPENSTATE_PLAYING,
+    SetPosition,
+    FMOD_OPENSTATE_SETPOSITION,
+    Max,
+    FMOD_OPENSTATE_MAX
+);
+
+bindable_enum!(
+    DspType,
+    FMOD_DSP_TYPE,
+    force_int FMOD_DSP_TYPE_FORCEINT;
+    Unknown,
+    FMOD_DSP_TYPE_UNKNOWN,
+    Mixer,
+    FMOD_DSP_TYPE_MIXER,
+    Oscillator,
+    FMOD_DSP_TYPE_OSCILLATOR,
+    Lowpass,
+    FMOD_DSP_TYPE_LOWPASS,
+    ItLowpass,
+    FMOD_DSP_TYPE_ITLOWPASS,
+    Highpass,
+    FMOD_DSP_TYPE_HIGHPASS,
+    Echo,
+    FMOD_DSP_TYPE_ECHO,
+    Fader,
+    FMOD_DSP_TYPE_FADER,
+    Flange,
+    FMOD_DSP_TYPE_FLANGE,
+    Distortion,
+    FMOD_DSP_TYPE_DISTORTION,
+    Normalize,
+    FMOD_DSP_TYPE_NORMALIZE,
+    Limiter,
+    FMOD_DSP_TYPE_LIMITER,
+    ParamEq,
+    FMOD_DSP_TYPE_PARAMEQ,
+    PitchShift,
+    FMOD_DSP_TYPE_PITCHSHIFT,
+    Chorus,
+    FMOD_DSP_TYPE_CHORUS,
+    VstPlugin,
+    FMOD_DSP_TYPE_VSTPLUGIN,
+    WinampPlugin,
+    FMOD_DSP_TYPE_WINAMPPLUGIN,
+    ItEcho,
+    FMOD_DSP_TYPE_ITECHO,
+    Compressor,
+    FMOD_DSP_TYPE_COMPRESSOR,
+    SfxReverb,
+    FMOD_DSP_TYPE_SFXREVERB,
+    LowpassSimple,
+    FMOD_DSP_TYPE_LOWPASS_SIMPLE,
+    Delay,
+    FMOD_DSP_TYPE_DELAY,
+    Tremolo,
+    FMOD_DSP_TYPE_TREMOLO,
+    LadspaPlugin,
+    FMOD_DSP_TYPE_LADSPAPLUGIN,
+    Send,
+    FMOD_DSP_TYPE_SEND,
+    Return,
+    FMOD_DSP_TYPE_RETURN,
+    HighpassSimple,
+    FMOD_DSP_TYPE_HIGHPASS_SIMPLE,
+    Pan,
+    FMOD_DSP_TYPE_PAN,
+    ThreeEq,
+    FMOD_DSP_TYPE_THREE_EQ,
+    Fft,
+    FMOD_DSP_TYPE_FFT,
+    LoudnessMeter,
+    FMOD_DSP_TYPE_LOUDNESS_METER,
+    EnvelopeFollower,
+    FMOD_DSP_TYPE_ENVELOPEFOLLOWER,
+    ConvolutionReverb,
+    FMOD_DSP_TYPE_CONVOLUTIONREVERB,
+    ChannelMix,
+    FMOD_DSP_TYPE_CHANNELMIX,
+    Transceiver,
+    FMOD_DSP_TYPE_TRANSCEIVER,
+    ObjectPan,
+    FMOD_DSP_TYPE_OBJECTPAN,
+    MultibandEq,
+    FMOD_DSP_TYPE_MULTIBAND_EQ,
+    Max,
+    FMOD_DSP_TYPE_MAX
+);
+
+bindable_enum!(
+    DspConnectionType,
+    FMOD_DSPCONNECTION_TYPE,
+    force_int FMOD_DSPCONNECTION_TYPE_FORCEINT;
+    Standard,
+    FMOD_DSPCONNECTION_TYPE_STANDARD,
+    Sidechain,
+    FMOD_DSPCONNECTION_TYPE_SIDECHAIN,
+    Send,
+    FMOD_DSPCONNECTION_TYPE_SEND,
+    SendSidechain,
+    FMOD_DSPCONNECTION_TYPE_SEND_SIDECHAIN,
+    Max,
+    FMOD_DSPCONNECTION_TYPE_MAX
+);
+
+bindable_enum!(
+    DspLowpass,
+    FMOD_DSP_LOWPASS;
+    Cutoff,
+    FMOD_DSP_LOWPASS_CUTOFF,
+    Resonance,
+    FMOD_DSP_LOWPASS_RESONANCE
+);
+
+bindable_enum!(
+    DspEcho,
+    FMOD_DSP_ECHO;
+    Delay,
+    FMOD_DSP_ECHO_DELAY,
+    Feedback,
+    FMOD_DSP_ECHO_FEEDBACK,
+    DryLevel,
+    FMOD_DSP_ECHO_DRYLEVEL,
+    WetLevel,
+    FMOD_DSP_ECHO_WETLEVEL
+);
+
+bindable_enum!(
+    Result,
+    FMOD_RESULT,
+    force_int FMOD_RESULT_FORCEINT;
+    Ok,
+    FMOD_OK,
+    Badcommand,
+    FMOD_ERR_BADCOMMAND,
+    ChannelAlloc,
+    FMOD_ERR_CHANNEL_ALLOC,
+    ChannelStolen,
+    FMOD_ERR_CHANNEL_STOLEN,
+    Dma,
+    FMOD_ERR_DMA,
+    DspConnection,
+    FMOD_ERR_DSP_CONNECTION,
+    DspDontprocess,
+    FMOD_ERR_DSP_DONTPROCESS,
+    DspFormat,
+    FMOD_ERR_DSP_FORMAT,
+    DspInuse,
+    FMOD_ERR_DSP_INUSE,
+    DspNotfound,
+    FMOD_ERR_DSP_NOTFOUND,
+    DspReserved,
+    FMOD_ERR_DSP_RESERVED,
+    DspSilence,
+    FMOD_ERR_DSP_SILENCE,
+    DspType,
+    FMOD_ERR_DSP_TYPE,
+    FileBad,
+    FMOD_ERR_FILE_BAD,
+    FileCouldnotseek,
+    FMOD_ERR_FILE_COULDNOTSEEK,
+    FileDiskejected,
+    FMOD_ERR_FILE_DISKEJECTED,
+    FileEof,
+    FMOD_ERR_FILE_EOF,
+    FileEndofdata,
+    FMOD_ERR_FILE_ENDOFDATA,
+    FileNotfound,
+    FMOD_ERR_FILE_NOTFOUND,
+    Format,
+    FMOD_ERR_FORMAT,
+    HeaderMismatch,
+    FMOD_ERR_HEADER_MISMATCH,
+    Http,
+    FMOD_ERR_HTTP,
+    HttpAccess,
+    FMOD_ERR_HTTP_ACCESS,
+    HttpProxyAuth,
+    FMOD_ERR_HTTP_PROXY_AUTH,
+    HttpServerError,
+    FMOD_ERR_HTTP_SERVER_ERROR,
+    HttpTimeout,
+    FMOD_ERR_HTTP_TIMEOUT,
+    Initialization,
+    FMOD_ERR_INITIALIZATION,
+    Initialized,
+    FMOD_ERR_INITIALIZED,
+    Internal,
+    FMOD_ERR_INTERNAL,
+    InvalidFloat,
+    FMOD_ERR_INVALID_FLOAT,
+    InvalidHandle,
+    FMOD_ERR_INVALID_HANDLE,
+    InvalidParam,
+    FMOD_ERR_INVALID_PARAM,
+    InvalidPosition,
+    FMOD_ERR_INVALID_POSITION,
+    InvalidSpeaker,
+    FMOD_ERR_INVALID_SPEAKER,
+    InvalidSyncpoint,
+    FMOD_ERR_INVALID_SYNCPOINT,
+    InvalidThread,
+    FMOD_ERR_INVALID_THREAD,
+    InvalidVector,
+    FMOD_ERR_INVALID_VECTOR,
+    Maxaudible,
+    FMOD_ERR_MAXAUDIBLE,
+    Memory,
+    FMOD_ERR_MEMORY,
+    MemoryCantpoint,
+    FMOD_ERR_MEMORY_CANTPOINT,
+    Needs3d,
+    FMOD_ERR_NEEDS3D,
+    Needshardware,
+    FMOD_ERR_NEEDSHARDWARE,
+    NetConnect,
+    FMOD_ERR_NET_CONNECT,
+    NetSocketError,
+    FMOD_ERR_NET_SOCKET_ERROR,
+    NetUrl,
+    FMOD_ERR_NET_URL,
+    NetWouldBlock,
+    FMOD_ERR_NET_WOULD_BLOCK,
+    Notready,
+    FMOD_ERR_NOTREADY,
+    OutputAllocated,
+    FMOD_ERR_OUTPUT_ALLOCATED,
+    OutputCreatebuffer,
+    FMOD_ERR_OUTPUT_CREATEBUFFER,
+    OutputDrivercall,
+    FMOD_ERR_OUTPUT_DRIVERCALL,
+    OutputFormat,
+    FMOD_ERR_OUTPUT_FORMAT,
+    OutputInit,
+    FMOD_ERR_OUTPUT_INIT,
+    OutputNodrivers,
+    FMOD_ERR_OUTPUT_NODRIVERS,
+    Plugin,
+    FMOD_ERR_PLUGIN,
+    PluginMissing,
+    FMOD_ERR_PLUGIN_MISSING,
+    PluginResource,
+    FMOD_ERR_PLUGIN_RESOURCE,
+    PluginVersion,
+    FMOD_ERR_PLUGIN_VERSION,
+    Record,
+    FMOD_ERR_RECORD,
+    ReverbChannelgroup,
+    FMOD_ERR_REVERB_CHANNELGROUP,
+    ReverbInstance,
+    FMOD_ERR_REVERB_INSTANCE,
+    Subsounds,
+    FMOD_ERR_SUBSOUNDS,
+    SubsoundAllocated,
+    FMOD_ERR_SUBSOUND_ALLOCATED,
+    SubsoundCantmove,
+    FMOD_ERR_SUBSOUND_CANTMOVE,
+    Tagnotfound,
+    FMOD_ERR_TAGNOTFOUND,
+    Toomanychannels,
+    FMOD_ERR_TOOMANYCHANNELS,
+    Truncated,
+    FMOD_ERR_TRUNCATED,
+    Unimplemented,
+    FMOD_ERR_UNIMPLEMENTED,
+    Uninitialized,
+    FMOD_ERR_UNINITIALIZED,
+    Unsupported,
+    FMOD_ERR_UNSUPPORTED,
+    Version,
+    FMOD_ERR_VERSION,
+    EventAlreadyLoaded,
+    FMOD_ERR_EVENT_ALREADY_LOADED,
+    EventLiveupdateBusy,
+    FMOD_ERR_EVENT_LIVEUPDATE_BUSY,
+    EventLiveupdateMismatch,
+    FMOD_ERR_EVENT_LIVEUPDATE_MISMATCH,
+    EventLiveupdateTimeout,
+    FMOD_ERR_EVENT_LIVEUPDATE_TIMEOUT,
+    EventNotfound,
+    FMOD_ERR_EVENT_NOTFOUND,
+    StudioUninitialized,
+    FMOD_ERR_STUDIO_UNINITIALIZED,
+    StudioNotLoaded,
+    FMOD_ERR_STUDIO_NOT_LOADED,
+    InvalidString,
+    FMOD_ERR_INVALID_STRING,
+    AlreadyLocked,
+    FMOD_ERR_ALREADY_LOCKED,
+    NotLocked,
+    FMOD_ERR_NOT_LOCKED,
+    RecordDisconnected,
+    FMOD_ERR_RECORD_DISCONNECTED,
+    Toomanysamples,
+    FMOD_ERR_TOOMANYSAMPLES
+);
+
+#[cfg(fsbank)]
+bindable_enum!(
+    FSBankFormat,
+    FSBANK_FORMAT,
+    force_int FSBANK_FORMAT_MAX;
+    Pcm,
+    FSBANK_FORMAT_PCM,
+    Xma,
+    FSBANK_FORMAT_XMA,
+    At9,
+    FSBANK_FORMAT_AT9,
+    Vorbis,
+    FSBANK_FORMAT_VORBIS,
+    Fadpcm,
+    FSBANK_FORMAT_FADPCM,
+    Opus,
+    FSBANK_FORMAT_OPUS
+);
+
+#[cfg(fsbank)]
+bindable_enum!(
+    FSBankState,
+    FSBANK_STATE;
+    Decoding,
+    FSBANK_STATE_DECODING,
+    Analysing,
+    FSBANK_STATE_ANALYSING,
+    Preprocessing,
+    FSBANK_STATE_PREPROCESSING,
+    Encoding,
+    FSBANK_STATE_ENCODING,
+    Writing,
+    FSBANK_STATE_WRITING,
+    Finished,
+    FSBANK_STATE_FINISHED,
+    Failed,
+    FSBANK_STATE_FAILED,
+    Warning,
+    FSBANK_STATE_WARNING
+);
+
+#[cfg(fsbank)]
+bindable_enum!(
+    FSBankResult,
+    FSBANK_RESULT;
+    Ok,
+    FSBANK_OK,
+    CacheChunknotfound,
+    FSBANK_ERR_CACHE_CHUNKNOTFOUND,
+    Cancelled,
+    FSBANK_ERR_CANCELLED,
+    CannotContinue,
+    FSBANK_ERR_CANNOT_CONTINUE,
+    Encoder,
+    FSBANK_ERR_ENCODER,
+    EncoderInit,
+    FSBANK_ERR_ENCODER_INIT,
+    EncoderNotsupported,
+    FSBANK_ERR_ENCODER_NOTSUPPORTED,
+    FileOs,
+    FSBANK_ERR_FILE_OS,
+    FileNotfound,
+    FSBANK_ERR_FILE_NOTFOUND,
+    Fmod,
+    FSBANK_ERR_FMOD,
+    Initialized,
+    FSBANK_ERR_INITIALIZED,
+    InvalidFormat,
+    FSBANK_ERR_INVALID_FORMAT,
+    InvalidParam,
+    FSBANK_ERR_INVALID_PARAM,
+    Memory,
+    FSBANK_ERR_MEMORY,
+    Uninitialized,
+    FSBANK_ERR_UNINITIALIZED,
+    WriterFormat,
+    FSBANK_ERR_WRITER_FORMAT,
+    WarnCannotloop,
+    FSBANK_WARN_CANNOTLOOP,
+    WarnIgnoredFilterhighfreq,
+    FSBANK_WARN_IGNORED_FILTERHIGHFREQ,
+    WarnIgnoredDisableseeking,
+    FSBANK_WARN_IGNORED_DISABLESEEKING,
+    WarnForcedDontwritenames,
+    FSBANK_WARN_FORCED_DONTWRITENAMES,
+    EncoderFileNotfound,
+    FSBANK_ERR_ENCODER_FILE_NOTFOUND,
+    EncoderFileBad,
+    FSBANK_ERR_ENCODER_FILE_BAD
+);
+
+pub fn bind_enums(module: impl magnus::Module) -> std::result::Result<(), magnus::Error> {
     ChannelControlCallbackType::bind(module)?;
+    Result::bind(module)?;
     LoadMemoryMode::bind(module)?;
     LoadingState::bind(module)?;
     ParameterType::bind(module)?;
@@ -181,6 +667,19 @@ pub fn bind_enums(module: impl magnus::Module) -> Result<(), magnus::Error> {
     StopMode::bind(module)?;
     PlaybackState::bind(module)?;
     InstanceType::bind(module)?;
+    SoundType::bind(module)?;
+    SoundFormat::bind(module)?;
+    OpenState::bind(module)?;
+    DspType::bind(module)?;
+    DspConnectionType::bind(module)?;
+    DspLowpass::bind(module)?;
+    DspEcho::bind(module)?;
+    #[cfg(fsbank)]
+    FSBankFormat::bind(module)?;
+    #[cfg(fsbank)]
+    FSBankState::bind(module)?;
+    #[cfg(fsbank)]
+    FSBankResult::bind(module)?;
 
     Ok(())
 }
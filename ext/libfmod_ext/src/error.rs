@@ -0,0 +1,412 @@
+// Copyright (C) 2022 Lily Lyons
+//
+// This file is part of libfmod.
+//
+// libfmod is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// libfmod is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
+
+use magnus::{Class, Module, Object};
+use once_cell::sync::OnceCell;
+
+// Ruby class name of every FMOD::Error subclass, by result code.
+// FMOD_OK is left out as it isn't an error.
+const RESULTS: &[(libfmod::FMOD_RESULT, &str)] = &[
+    (libfmod::FMOD_RESULT::FMOD_ERR_BADCOMMAND, "BadCommand"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_CHANNEL_ALLOC, "ChannelAlloc"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_CHANNEL_STOLEN,
+        "ChannelStolen",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_DMA, "Dma"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_DSP_CONNECTION,
+        "DspConnection",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_DSP_DONTPROCESS,
+        "DspDontProcess",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_DSP_FORMAT, "DspFormat"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_DSP_INUSE, "DspInUse"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_DSP_NOTFOUND, "DspNotFound"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_DSP_RESERVED, "DspReserved"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_DSP_SILENCE, "DspSilence"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_DSP_TYPE, "DspType"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_FILE_BAD, "FileBad"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_FILE_COULDNOTSEEK,
+        "FileCouldNotSeek",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_FILE_DISKEJECTED,
+        "FileDiskEjected",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_FILE_EOF, "FileEof"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_FILE_ENDOFDATA,
+        "FileEndOfData",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_FILE_NOTFOUND, "FileNotFound"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_FORMAT, "Format"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_HEADER_MISMATCH,
+        "HeaderMismatch",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_HTTP, "Http"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_HTTP_ACCESS, "HttpAccess"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_HTTP_PROXY_AUTH,
+        "HttpProxyAuth",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_HTTP_SERVER_ERROR,
+        "HttpServerError",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_HTTP_TIMEOUT, "HttpTimeout"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_INITIALIZATION,
+        "Initialization",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_INITIALIZED, "Initialized"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_INTERNAL, "Internal"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_INVALID_FLOAT, "InvalidFloat"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_HANDLE,
+        "InvalidHandle",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_INVALID_PARAM, "InvalidParam"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_POSITION,
+        "InvalidPosition",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_SPEAKER,
+        "InvalidSpeaker",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_SYNCPOINT,
+        "InvalidSyncPoint",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_THREAD,
+        "InvalidThread",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_VECTOR,
+        "InvalidVector",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_MAXAUDIBLE, "MaxAudible"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_MEMORY, "Memory"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_MEMORY_CANTPOINT,
+        "MemoryCantPoint",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_NEEDS3D, "Needs3D"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_NEEDSHARDWARE,
+        "NeedsHardware",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_NET_CONNECT, "NetConnect"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_NET_SOCKET_ERROR,
+        "NetSocketError",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_NET_URL, "NetUrl"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_NET_WOULD_BLOCK,
+        "NetWouldBlock",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_NOTREADY, "NotReady"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_ALLOCATED,
+        "OutputAllocated",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_CREATEBUFFER,
+        "OutputCreateBuffer",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_DRIVERCALL,
+        "OutputDriverCall",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_FORMAT, "OutputFormat"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_INIT, "OutputInit"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_NODRIVERS,
+        "OutputNoDrivers",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_PLUGIN, "Plugin"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_PLUGIN_MISSING,
+        "PluginMissing",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_PLUGIN_RESOURCE,
+        "PluginResource",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_PLUGIN_VERSION,
+        "PluginVersion",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_RECORD, "Record"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_REVERB_CHANNELGROUP,
+        "ReverbChannelGroup",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_REVERB_INSTANCE,
+        "ReverbInstance",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_SUBSOUNDS, "Subsounds"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_SUBSOUND_ALLOCATED,
+        "SubsoundAllocated",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_SUBSOUND_CANTMOVE,
+        "SubsoundCantMove",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_TAGNOTFOUND, "TagNotFound"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_TOOMANYCHANNELS,
+        "TooManyChannels",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_TRUNCATED, "Truncated"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_UNIMPLEMENTED,
+        "Unimplemented",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_UNINITIALIZED,
+        "Uninitialized",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_UNSUPPORTED, "Unsupported"),
+    (libfmod::FMOD_RESULT::FMOD_ERR_VERSION, "Version"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_EVENT_ALREADY_LOADED,
+        "EventAlreadyLoaded",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_EVENT_LIVEUPDATE_BUSY,
+        "EventLiveUpdateBusy",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_EVENT_LIVEUPDATE_MISMATCH,
+        "EventLiveUpdateMismatch",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_EVENT_LIVEUPDATE_TIMEOUT,
+        "EventLiveUpdateTimeout",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_EVENT_NOTFOUND,
+        "EventNotFound",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_STUDIO_UNINITIALIZED,
+        "StudioUninitialized",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_STUDIO_NOT_LOADED,
+        "StudioNotLoaded",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_STRING,
+        "InvalidString",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_ALREADY_LOCKED,
+        "AlreadyLocked",
+    ),
+    (libfmod::FMOD_RESULT::FMOD_ERR_NOT_LOCKED, "NotLocked"),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_RECORD_DISCONNECTED,
+        "RecordDisconnected",
+    ),
+    (
+        libfmod::FMOD_RESULT::FMOD_ERR_TOOMANYSAMPLES,
+        "TooManySamples",
+    ),
+];
+
+fn message(result: libfmod::FMOD_RESULT) -> &'static str {
+    match result {
+        libfmod::FMOD_RESULT::FMOD_OK => "No errors.",
+        libfmod::FMOD_RESULT::FMOD_ERR_BADCOMMAND => "Tried to call a function on a data type that does not allow this type of functionality (ie calling Sound::lock on a streaming sound).",
+        libfmod::FMOD_RESULT::FMOD_ERR_CHANNEL_ALLOC => "Error trying to allocate a channel.",
+        libfmod::FMOD_RESULT::FMOD_ERR_CHANNEL_STOLEN => "The specified channel has been reused to play another sound.",
+        libfmod::FMOD_RESULT::FMOD_ERR_DMA => "DMA Failure.  See debug output for more information.",
+        libfmod::FMOD_RESULT::FMOD_ERR_DSP_CONNECTION => "DSP connection error.  Connection possibly caused a cyclic dependency or connected dsps with incompatible buffer counts.",
+        libfmod::FMOD_RESULT::FMOD_ERR_DSP_DONTPROCESS => "DSP return code from a DSP process query callback.  Tells mixer not to call the process callback and therefore not consume CPU.  Use this to optimize the DSP graph.",
+        libfmod::FMOD_RESULT::FMOD_ERR_DSP_FORMAT => "DSP Format error.  A DSP unit may have attempted to connect to this network with the wrong format, or a matrix may have been set with the wrong size if the target unit has a specified channel map.",
+        libfmod::FMOD_RESULT::FMOD_ERR_DSP_INUSE => "DSP is already in the mixer's DSP network. It must be removed before being reinserted or released.",
+        libfmod::FMOD_RESULT::FMOD_ERR_DSP_NOTFOUND => "DSP connection error.  Couldn't find the DSP unit specified.",
+        libfmod::FMOD_RESULT::FMOD_ERR_DSP_RESERVED => "DSP operation error.  Cannot perform operation on this DSP as it is reserved by the system.",
+        libfmod::FMOD_RESULT::FMOD_ERR_DSP_SILENCE => "DSP return code from a DSP process query callback.  Tells mixer silence would be produced from read, so go idle and not consume CPU.  Use this to optimize the DSP graph.",
+        libfmod::FMOD_RESULT::FMOD_ERR_DSP_TYPE => "DSP operation cannot be performed on a DSP of this type.",
+        libfmod::FMOD_RESULT::FMOD_ERR_FILE_BAD => "Error loading file.",
+        libfmod::FMOD_RESULT::FMOD_ERR_FILE_COULDNOTSEEK => "Couldn't perform seek operation.  This is a limitation of the medium (ie netstreams) or the file format.",
+        libfmod::FMOD_RESULT::FMOD_ERR_FILE_DISKEJECTED => "Media was ejected while reading.",
+        libfmod::FMOD_RESULT::FMOD_ERR_FILE_EOF => "End of file unexpectedly reached while trying to read essential data (truncated?).",
+        libfmod::FMOD_RESULT::FMOD_ERR_FILE_ENDOFDATA => "End of current chunk reached while trying to read data.",
+        libfmod::FMOD_RESULT::FMOD_ERR_FILE_NOTFOUND => "File not found.",
+        libfmod::FMOD_RESULT::FMOD_ERR_FORMAT => "Unsupported file or audio format.",
+        libfmod::FMOD_RESULT::FMOD_ERR_HEADER_MISMATCH => "There is a version mismatch between the FMOD header and either the FMOD Studio library or the FMOD Low Level library.",
+        libfmod::FMOD_RESULT::FMOD_ERR_HTTP => "A HTTP error occurred. This is a catch-all for HTTP errors not listed elsewhere.",
+        libfmod::FMOD_RESULT::FMOD_ERR_HTTP_ACCESS => "The specified resource requires authentication or is forbidden.",
+        libfmod::FMOD_RESULT::FMOD_ERR_HTTP_PROXY_AUTH => "Proxy authentication is required to access the specified resource.",
+        libfmod::FMOD_RESULT::FMOD_ERR_HTTP_SERVER_ERROR => "A HTTP server error occurred.",
+        libfmod::FMOD_RESULT::FMOD_ERR_HTTP_TIMEOUT => "The HTTP request timed out.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INITIALIZATION => "FMOD was not initialized correctly to support this function.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INITIALIZED => "Cannot call this command after System::init.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INTERNAL => "An error occurred that wasn't supposed to.  Contact support.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_FLOAT => "Value passed in was a NaN, Inf or denormalized float.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_HANDLE => "An invalid object handle was used.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_PARAM => "An invalid parameter was passed to this function.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_POSITION => "An invalid seek position was passed to this function.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_SPEAKER => "An invalid speaker was passed to this function based on the current speaker mode.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_SYNCPOINT => "The syncpoint did not come from this sound handle.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_THREAD => "Tried to call a function on a thread that is not supported.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_VECTOR => "The vectors passed in are not unit length, or perpendicular.",
+        libfmod::FMOD_RESULT::FMOD_ERR_MAXAUDIBLE => "Reached maximum audible playback count for this sound's soundgroup.",
+        libfmod::FMOD_RESULT::FMOD_ERR_MEMORY => "Not enough memory or resources.",
+        libfmod::FMOD_RESULT::FMOD_ERR_MEMORY_CANTPOINT => "Can't use FMOD_OPENMEMORY_POINT on non PCM source data, or non mp3/xma/adpcm data if FMOD_CREATECOMPRESSEDSAMPLE was used.",
+        libfmod::FMOD_RESULT::FMOD_ERR_NEEDS3D => "Tried to call a command on a 2d sound when the command was meant for 3d sound.",
+        libfmod::FMOD_RESULT::FMOD_ERR_NEEDSHARDWARE => "Tried to use a feature that requires hardware support.",
+        libfmod::FMOD_RESULT::FMOD_ERR_NET_CONNECT => "Couldn't connect to the specified host.",
+        libfmod::FMOD_RESULT::FMOD_ERR_NET_SOCKET_ERROR => "A socket error occurred.  This is a catch-all for socket-related errors not listed elsewhere.",
+        libfmod::FMOD_RESULT::FMOD_ERR_NET_URL => "The specified URL couldn't be resolved.",
+        libfmod::FMOD_RESULT::FMOD_ERR_NET_WOULD_BLOCK => "Operation on a non-blocking socket could not complete immediately.",
+        libfmod::FMOD_RESULT::FMOD_ERR_NOTREADY => "Operation could not be performed because specified sound/DSP connection is not ready.",
+        libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_ALLOCATED => "Error initializing output device, but more specifically, the output device is already in use and cannot be reused.",
+        libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_CREATEBUFFER => "Error creating hardware sound buffer.",
+        libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_DRIVERCALL => "A call to a standard soundcard driver failed, which could possibly mean a bug in the driver or resources were missing or exhausted.",
+        libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_FORMAT => "Soundcard does not support the specified format.",
+        libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_INIT => "Error initializing output device.",
+        libfmod::FMOD_RESULT::FMOD_ERR_OUTPUT_NODRIVERS => "The output device has no drivers installed.  If pre-init, FMOD_OUTPUT_NOSOUND is selected as the output mode.  If post-init, the function just fails.",
+        libfmod::FMOD_RESULT::FMOD_ERR_PLUGIN => "An unspecified error has been returned from a plugin.",
+        libfmod::FMOD_RESULT::FMOD_ERR_PLUGIN_MISSING => "A requested output, dsp unit type or codec was not available.",
+        libfmod::FMOD_RESULT::FMOD_ERR_PLUGIN_RESOURCE => "A resource that the plugin requires cannot be allocated or found. (ie the DLS file for MIDI playback)",
+        libfmod::FMOD_RESULT::FMOD_ERR_PLUGIN_VERSION => "A plugin was built with an unsupported SDK version.",
+        libfmod::FMOD_RESULT::FMOD_ERR_RECORD => "An error occurred trying to initialize the recording device.",
+        libfmod::FMOD_RESULT::FMOD_ERR_REVERB_CHANNELGROUP => "Reverb properties cannot be set on this channel because a parent channelgroup owns the reverb connection.",
+        libfmod::FMOD_RESULT::FMOD_ERR_REVERB_INSTANCE => "Specified instance in FMOD_REVERB_PROPERTIES couldn't be set. Most likely because it is an invalid instance number or the reverb doesn't exist.",
+        libfmod::FMOD_RESULT::FMOD_ERR_SUBSOUNDS => "The error occurred because the sound referenced contains subsounds when it shouldn't have, or it doesn't contain subsounds when it should have.  The operation may also not be able to be performed on a parent sound.",
+        libfmod::FMOD_RESULT::FMOD_ERR_SUBSOUND_ALLOCATED => "This subsound is already being used by another sound, you cannot have more than one parent to a sound.  Null out the other parent's entry first.",
+        libfmod::FMOD_RESULT::FMOD_ERR_SUBSOUND_CANTMOVE => "Shared subsounds cannot be replaced or moved from their parent stream, such as when the parent stream is an FSB file.",
+        libfmod::FMOD_RESULT::FMOD_ERR_TAGNOTFOUND => "The specified tag could not be found or there are no tags.",
+        libfmod::FMOD_RESULT::FMOD_ERR_TOOMANYCHANNELS => "The sound created exceeds the allowable input channel count.  This can be increased using the 'maxinputchannels' parameter in System::setSoftwareFormat.",
+        libfmod::FMOD_RESULT::FMOD_ERR_TRUNCATED => "The retrieved string is too long to fit in the supplied buffer and has been truncated.",
+        libfmod::FMOD_RESULT::FMOD_ERR_UNIMPLEMENTED => "Something in FMOD hasn't been implemented when it should be! contact support!",
+        libfmod::FMOD_RESULT::FMOD_ERR_UNINITIALIZED => "This command failed because System::init or System::setDriver was not called.",
+        libfmod::FMOD_RESULT::FMOD_ERR_UNSUPPORTED => "A command issued was not supported by this object.  Possibly a plugin without certain callbacks specified.",
+        libfmod::FMOD_RESULT::FMOD_ERR_VERSION => "The version number of this file format is not supported.",
+        libfmod::FMOD_RESULT::FMOD_ERR_EVENT_ALREADY_LOADED => "The specified bank has already been loaded.",
+        libfmod::FMOD_RESULT::FMOD_ERR_EVENT_LIVEUPDATE_BUSY => "The live update connection failed due to the game already being connected.",
+        libfmod::FMOD_RESULT::FMOD_ERR_EVENT_LIVEUPDATE_MISMATCH => "The live update connection failed due to the game data being out of sync with the tool.",
+        libfmod::FMOD_RESULT::FMOD_ERR_EVENT_LIVEUPDATE_TIMEOUT => "The live update connection timed out.",
+        libfmod::FMOD_RESULT::FMOD_ERR_EVENT_NOTFOUND => "The requested event, parameter, bus or vca could not be found.",
+        libfmod::FMOD_RESULT::FMOD_ERR_STUDIO_UNINITIALIZED => "The Studio::System object is not yet initialized.",
+        libfmod::FMOD_RESULT::FMOD_ERR_STUDIO_NOT_LOADED => "The specified resource is not loaded, so it can't be unloaded.",
+        libfmod::FMOD_RESULT::FMOD_ERR_INVALID_STRING => "An invalid string was passed to this function.",
+        libfmod::FMOD_RESULT::FMOD_ERR_ALREADY_LOCKED => "The specified resource is already locked.",
+        libfmod::FMOD_RESULT::FMOD_ERR_NOT_LOCKED => "The specified resource is not locked, so it can't be unlocked.",
+        libfmod::FMOD_RESULT::FMOD_ERR_RECORD_DISCONNECTED => "The specified recording driver has been disconnected.",
+        libfmod::FMOD_RESULT::FMOD_ERR_TOOMANYSAMPLES => "The length provided exceeds the allowable limit.",
+        libfmod::FMOD_RESULT::FMOD_RESULT_FORCEINT => unreachable!(),
+    }
+}
+
+// An exception class defined by bind. Classes are constants and never garbage collected,
+// so any thread can hold on to one without calling into Ruby.
+#[derive(Clone, Copy)]
+pub(crate) struct ErrorClass(pub(crate) magnus::ExceptionClass);
+
+unsafe impl Send for ErrorClass {}
+unsafe impl Sync for ErrorClass {}
+
+// The FMOD::Error subclasses, in the same order as RESULTS.
+static CLASSES: OnceCell<Vec<ErrorClass>> = OnceCell::new();
+
+// Creating an exception calls into Ruby, which only Ruby threads can do (with or without the GVL).
+// Any other thread, like one of FMOD's, gets an error that Ruby creates once it is raised at the method boundary instead.
+// That exception has no @function, and its code comes from the class (see lib/libfmod/error.rb).
+pub(crate) fn new_exception<F>(class: ErrorClass, message: String, ivars: F) -> magnus::Error
+where
+    F: FnOnce(magnus::Exception) -> Result<(), magnus::Error>,
+{
+    if unsafe { rb_sys::ruby_native_thread_p() } == 0 {
+        return magnus::Error::new(class.0, message);
+    }
+
+    let mut args = Some((message, ivars));
+    let exception = || -> Result<magnus::Exception, magnus::Error> {
+        let (message, ivars) = args.take().unwrap();
+        let exception = class.0.new_instance((message,))?;
+        ivars(exception)?;
+
+        Ok(exception)
+    };
+
+    // FMOD calls that may block are made without the GVL, and their errors are built right there.
+    match unsafe { crate::thread::with_gvl(exception) } {
+        Ok(e) => e.into(),
+        Err(e) => e,
+    }
+}
+
+// Builds an instance of the FMOD::Error subclass for `code`.
+// `function` is the FFI function that failed, if we know it.
+pub(crate) fn fmod_error(code: std::ffi::c_int, function: Option<&str>) -> magnus::Error {
+    let index = RESULTS
+        .iter()
+        .position(|(r, _)| *r as std::ffi::c_int == code);
+    let (Some(index), Some(classes)) = (index, CLASSES.get()) else {
+        return magnus::Error::new(
+            magnus::exception::runtime_error(),
+            format!("unknown FMOD result {code}"),
+        );
+    };
+    let result = RESULTS[index].0;
+
+    let message = match function {
+        Some(function) => format!("{function} failed: {}", message(result)),
+        None => message(result).to_string(),
+    };
+
+    new_exception(classes[index], message, |exception| {
+        exception.ivar_set("@code", code)?;
+        exception.ivar_set("@result", crate::enums::Result::from(result))?;
+        exception.ivar_set("@function", function)
+    })
+}
+
+pub fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    let error = module.define_error("Error", magnus::exception::standard_error())?;
+    // code and result are defined in lib/libfmod/error.rb.
+    let _: magnus::Value = error.funcall("attr_reader", (magnus::Symbol::new("function"),))?;
+
+    let classes = RESULTS
+        .iter()
+        .map(|&(result, name)| {
+            let class = error.define_error(name, error)?;
+            class.const_set("CODE", result as std::ffi::c_int)?;
+
+            Ok(ErrorClass(class))
+        })
+        .collect::<Result<Vec<_>, magnus::Error>>()?;
+    // Only fails if bind is called twice, and the classes would be the same anyway.
+    let _ = CLASSES.set(classes);
+
+    Ok(())
+}
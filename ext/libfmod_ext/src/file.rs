@@ -0,0 +1,442 @@
+// Copyright (C) 2022 Lily Lyons
+//
+// This file is part of libfmod.
+//
+// libfmod is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// libfmod is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
+
+// FMOD file callbacks that read through Ruby objects.
+// Anything that responds to read, seek and close works (and size, if it has it), so IO, StringIO, or your own archive entries.
+//
+// FMOD calls these from whatever thread is loading, which is usually not a Ruby thread.
+// Every callback goes through call_ruby, which runs the Ruby side on a Ruby thread with the GVL.
+
+use std::ffi::{c_char, c_uint, c_void, CStr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use magnus::value::BoxValue;
+
+use crate::callback::{call_ruby, report_error};
+
+// One Ruby IO that FMOD has open.
+// The same file may be opened by FMOD several times (banks are, for streaming their sample data) so it is reference counted.
+// Whoever drops the last reference closes the IO.
+pub(crate) struct RubyFile {
+    io: BoxValue<magnus::Value>,
+    references: AtomicUsize,
+}
+
+impl RubyFile {
+    // Must be called on a Ruby thread.
+    pub(crate) fn new(io: magnus::Value) -> *mut Self {
+        Box::into_raw(Box::new(Self {
+            io: BoxValue::new(io),
+            references: AtomicUsize::new(1),
+        }))
+    }
+
+    // Must be called on a Ruby thread.
+    pub(crate) unsafe fn release(ptr: *mut Self) -> Result<(), magnus::Error> {
+        if (*ptr).references.fetch_sub(1, Ordering::AcqRel) == 1 {
+            let file = Box::from_raw(ptr);
+            let _: magnus::Value = file.io.funcall("close", ())?;
+        }
+
+        Ok(())
+    }
+
+    fn size(io: magnus::Value) -> Result<c_uint, magnus::Error> {
+        if io.respond_to("size", false)? {
+            return io.funcall("size", ());
+        }
+
+        // IO::SEEK_END
+        let _: magnus::Value = io.funcall("seek", (0, 2))?;
+        let size = io.funcall("pos", ())?;
+        let _: magnus::Value = io.funcall("seek", (0,))?;
+
+        Ok(size)
+    }
+}
+
+// The object passed to System#set_file_system, which is asked to open files by name, and the system it was set on.
+// FMOD doesn't tell file callbacks which system they belong to, so only one system at a time can have a handler.
+struct FileSystem {
+    system: usize,
+    handler: BoxValue<magnus::Value>,
+}
+
+// Only ever touched on Ruby threads.
+unsafe impl Send for FileSystem {}
+
+static FILE_SYSTEM: Mutex<Option<FileSystem>> = Mutex::new(None);
+
+// Raises if a different system already has a file system handler, as it would silently lose it otherwise.
+pub(crate) fn check_file_system(
+    system: *mut libfmod::ffi::FMOD_SYSTEM,
+) -> Result<(), magnus::Error> {
+    match FILE_SYSTEM.lock().unwrap().as_ref() {
+        Some(file_system) if file_system.system != system as usize => Err(magnus::Error::new(
+            magnus::exception::runtime_error(),
+            "another FMOD::Core::System already has a file system handler, set it to nil there first",
+        )),
+        _ => Ok(()),
+    }
+}
+
+// Must be called on a Ruby thread, after check_file_system.
+// Passing nil only lets go of the handler if it belongs to this system.
+pub(crate) fn set_file_system(
+    system: *mut libfmod::ffi::FMOD_SYSTEM,
+    handler: Option<magnus::Value>,
+) {
+    let mut file_system = FILE_SYSTEM.lock().unwrap();
+
+    match handler {
+        Some(handler) => {
+            *file_system = Some(FileSystem {
+                system: system as usize,
+                handler: BoxValue::new(handler),
+            })
+        }
+        None if file_system
+            .as_ref()
+            .map_or(false, |f| f.system == system as usize) =>
+        {
+            *file_system = None
+        }
+        None => {}
+    }
+}
+
+// Runs func on a Ruby thread. Exceptions are reported like any other callback's, and FMOD is told the file is bad.
+// So is FMOD when func can't be run because the event thread is shut down.
+// Pointers are passed around as usizes, as raw pointers aren't Send.
+fn with_ruby<F>(func: F) -> libfmod::ffi::FMOD_RESULT
+where
+    F: FnOnce() -> Result<libfmod::ffi::FMOD_RESULT, magnus::Error> + Send + 'static,
+{
+    call_ruby(move || {
+        func().unwrap_or_else(|e| {
+            report_error(e);
+            libfmod::ffi::FMOD_ERR_FILE_BAD
+        })
+    })
+    .unwrap_or(libfmod::ffi::FMOD_ERR_FILE_BAD)
+}
+
+// Opens `name` through the file system handler. A nil IO means the file does not exist.
+pub(crate) unsafe extern "C" fn file_system_open(
+    name: *const c_char,
+    filesize: *mut c_uint,
+    handle: *mut *mut c_void,
+    _userdata: *mut c_void,
+) -> libfmod::ffi::FMOD_RESULT {
+    let name = CStr::from_ptr(name).to_string_lossy().into_owned();
+    let (filesize, handle) = (filesize as usize, handle as usize);
+
+    with_ruby(move || {
+        let Some(handler) = FILE_SYSTEM.lock().unwrap().as_ref().map(|f| *f.handler) else {
+            return Ok(libfmod::ffi::FMOD_ERR_FILE_NOTFOUND);
+        };
+
+        let Some(io) = handler.funcall::<_, _, Option<magnus::Value>>("open", (name,))? else {
+            return Ok(libfmod::ffi::FMOD_ERR_FILE_NOTFOUND);
+        };
+
+        *(filesize as *mut c_uint) = RubyFile::size(io)?;
+        *(handle as *mut *mut c_void) = RubyFile::new(io) as _;
+
+        Ok(libfmod::ffi::FMOD_OK)
+    })
+}
+
+// Banks loaded with Studio::System#load_bank_custom already have their RubyFile, which is passed as the user data.
+pub(crate) unsafe extern "C" fn bank_open(
+    _name: *const c_char,
+    filesize: *mut c_uint,
+    handle: *mut *mut c_void,
+    userdata: *mut c_void,
+) -> libfmod::ffi::FMOD_RESULT {
+    let (filesize, handle, file) = (filesize as usize, handle as usize, userdata as usize);
+
+    with_ruby(move || {
+        let file = &*(file as *const RubyFile);
+
+        let _: magnus::Value = file.io.funcall("seek", (0,))?;
+        *(filesize as *mut c_uint) = RubyFile::size(*file.io)?;
+        file.references.fetch_add(1, Ordering::AcqRel);
+        *(handle as *mut *mut c_void) = file as *const RubyFile as _;
+
+        Ok(libfmod::ffi::FMOD_OK)
+    })
+}
+
+pub(crate) unsafe extern "C" fn file_close(
+    handle: *mut c_void,
+    _userdata: *mut c_void,
+) -> libfmod::ffi::FMOD_RESULT {
+    let handle = handle as usize;
+
+    with_ruby(move || {
+        RubyFile::release(handle as *mut RubyFile)?;
+
+        Ok(libfmod::ffi::FMOD_OK)
+    })
+}
+
+pub(crate) unsafe extern "C" fn file_read(
+    handle: *mut c_void,
+    buffer: *mut c_void,
+    sizebytes: c_uint,
+    bytesread: *mut c_uint,
+    _userdata: *mut c_void,
+) -> libfmod::ffi::FMOD_RESULT {
+    let (handle, buffer, bytesread) = (handle as usize, buffer as usize, bytesread as usize);
+
+    with_ruby(move || {
+        let file = &*(handle as *const RubyFile);
+
+        // nil is the end of the file.
+        let data: Option<magnus::RString> = file.io.funcall("read", (sizebytes,))?;
+        let data = data.as_ref().map_or(&[][..], |d| d.as_slice());
+        let length = data.len().min(sizebytes as usize);
+
+        std::ptr::copy_nonoverlapping(data.as_ptr(), buffer as *mut u8, length);
+        *(bytesread as *mut c_uint) = length as c_uint;
+
+        if length < sizebytes as usize {
+            Ok(libfmod::ffi::FMOD_ERR_FILE_EOF)
+        } else {
+            Ok(libfmod::ffi::FMOD_OK)
+        }
+    })
+}
+
+pub(crate) unsafe extern "C" fn file_seek(
+    handle: *mut c_void,
+    pos: c_uint,
+    _userdata: *mut c_void,
+) -> libfmod::ffi::FMOD_RESULT {
+    let handle = handle as usize;
+
+    with_ruby(move || {
+        let file = &*(handle as *const RubyFile);
+        let _: magnus::Value = file.io.funcall("seek", (pos,))?;
+
+        Ok(libfmod::ffi::FMOD_OK)
+    })
+}
+
+// FMOD lets async reads finish whenever they like. Ruby IO is synchronous anyway, so we finish them before returning.
+pub(crate) unsafe extern "C" fn file_async_read(
+    info: *mut libfmod::ffi::FMOD_ASYNCREADINFO,
+    userdata: *mut c_void,
+) -> libfmod::ffi::FMOD_RESULT {
+    let info = &mut *info;
+
+    let mut result = file_seek(info.handle, info.offset, userdata);
+    if result == libfmod::ffi::FMOD_OK {
+        result = file_read(
+            info.handle,
+            info.buffer,
+            info.sizebytes,
+            &mut info.bytesread,
+            userdata,
+        );
+    }
+
+    if let Some(done) = info.done {
+        done(info, result);
+    }
+
+    libfmod::ffi::FMOD_OK
+}
+
+// Reads are done by the time file_async_read returns, so there is never anything to cancel.
+pub(crate) unsafe extern "C" fn file_async_cancel(
+    _info: *mut libfmod::ffi::FMOD_ASYNCREADINFO,
+    _userdata: *mut c_void,
+) -> libfmod::ffi::FMOD_RESULT {
+    libfmod::ffi::FMOD_OK
+}
+
+// A bank read straight out of a Ruby string, without calling into Ruby at all.
+// The string is frozen and rooted (which also pins it) for as long as FMOD can read from it.
+pub(crate) struct MemoryFile {
+    #[allow(dead_code)]
+    data: BoxValue<magnus::RString>,
+    ptr: *const u8,
+    len: usize,
+    references: AtomicUsize,
+}
+
+// Each time FMOD opens a MemoryFile it gets its own position in it.
+struct MemoryCursor {
+    file: *mut MemoryFile,
+    position: usize,
+}
+
+impl MemoryFile {
+    // Must be called on a Ruby thread.
+    pub(crate) fn new(data: magnus::RString) -> *mut Self {
+        data.freeze();
+        let slice = unsafe { data.as_slice() };
+
+        Box::into_raw(Box::new(Self {
+            ptr: slice.as_ptr(),
+            len: slice.len(),
+            data: BoxValue::new(data),
+            references: AtomicUsize::new(1),
+        }))
+    }
+
+    // Can be called from any thread, the string is unrooted on a Ruby thread.
+    pub(crate) unsafe fn release(ptr: *mut Self) {
+        if (*ptr).references.fetch_sub(1, Ordering::AcqRel) == 1 {
+            crate::callback::UserDataRelease::create(Box::from_raw(ptr));
+        }
+    }
+}
+
+pub(crate) unsafe extern "C" fn memory_open(
+    _name: *const c_char,
+    filesize: *mut c_uint,
+    handle: *mut *mut c_void,
+    userdata: *mut c_void,
+) -> libfmod::ffi::FMOD_RESULT {
+    let file = userdata as *mut MemoryFile;
+    (*file).references.fetch_add(1, Ordering::AcqRel);
+
+    *filesize = (*file).len as c_uint;
+    *handle = Box::into_raw(Box::new(MemoryCursor { file, position: 0 })) as _;
+
+    libfmod::ffi::FMOD_OK
+}
+
+pub(crate) unsafe extern "C" fn memory_close(
+    handle: *mut c_void,
+    _userdata: *mut c_void,
+) -> libfmod::ffi::FMOD_RESULT {
+    let cursor = Box::from_raw(handle as *mut MemoryCursor);
+    MemoryFile::release(cursor.file);
+
+    libfmod::ffi::FMOD_OK
+}
+
+pub(crate) unsafe extern "C" fn memory_read(
+    handle: *mut c_void,
+    buffer: *mut c_void,
+    sizebytes: c_uint,
+    bytesread: *mut c_uint,
+    _userdata: *mut c_void,
+) -> libfmod::ffi::FMOD_RESULT {
+    let cursor = &mut *(handle as *mut MemoryCursor);
+    let file = &*cursor.file;
+
+    let start = cursor.position.min(file.len);
+    let length = (file.len - start).min(sizebytes as usize);
+    std::ptr::copy_nonoverlapping(file.ptr.add(start), buffer as *mut u8, length);
+
+    cursor.position = start + length;
+    *bytesread = length as c_uint;
+
+    if length < sizebytes as usize {
+        libfmod::ffi::FMOD_ERR_FILE_EOF
+    } else {
+        libfmod::ffi::FMOD_OK
+    }
+}
+
+pub(crate) unsafe extern "C" fn memory_seek(
+    handle: *mut c_void,
+    pos: c_uint,
+    _userdata: *mut c_void,
+) -> libfmod::ffi::FMOD_RESULT {
+    let cursor = &mut *(handle as *mut MemoryCursor);
+    if pos as usize > (*cursor.file).len {
+        return libfmod::ffi::FMOD_ERR_FILE_COULDNOTSEEK;
+    }
+    cursor.position = pos as usize;
+
+    libfmod::ffi::FMOD_OK
+}
+
+// What a bank was loaded from, if FMOD keeps reading from it after loading.
+// This is the bank's user data, and it's released once the bank has finished unloading (see release_unloaded_banks).
+pub(crate) enum BankSource {
+    Io(*mut RubyFile),
+    Memory(*mut MemoryFile),
+    // Loaded with LoadMemoryMode::MemoryPoint, so FMOD uses the string itself.
+    Pinned(BoxValue<magnus::RString>),
+}
+
+impl BankSource {
+    // Must be called on a Ruby thread.
+    pub(crate) unsafe fn release(self) -> Result<(), magnus::Error> {
+        match self {
+            Self::Io(file) => RubyFile::release(file),
+            Self::Memory(file) => {
+                MemoryFile::release(file);
+                Ok(())
+            }
+            Self::Pinned(data) => {
+                drop(data);
+                Ok(())
+            }
+        }
+    }
+}
+
+// Banks FMOD is still unloading, and what they were loaded from.
+// Unloading finishes asynchronously and FMOD reads from the source until then, so it's held on to until the bank is gone.
+struct UnloadingBank(libfmod::Bank, BankSource);
+
+// Only ever touched on Ruby threads.
+unsafe impl Send for UnloadingBank {}
+
+static UNLOADING_BANKS: Mutex<Vec<UnloadingBank>> = Mutex::new(Vec::new());
+
+// Must be called on a Ruby thread, after FMOD has been told to unload the bank.
+pub(crate) fn release_when_unloaded(bank: libfmod::Bank, source: BankSource) {
+    UNLOADING_BANKS
+        .lock()
+        .unwrap()
+        .push(UnloadingBank(bank, source));
+}
+
+// Releases the source of every bank that has finished unloading. Called by Studio::System#update and #release.
+// Must be called on a Ruby thread.
+pub(crate) fn release_unloaded_banks() -> Result<(), magnus::Error> {
+    // Releasing a source can call into Ruby (to close it), which could unload more banks.
+    let banks = std::mem::take(&mut *UNLOADING_BANKS.lock().unwrap());
+    let mut pending = Vec::with_capacity(banks.len());
+    let mut error = None;
+
+    for UnloadingBank(bank, source) in banks {
+        // Once the bank is gone its handle is invalid, and FMOD errors instead of saying it's unloaded.
+        match bank.get_loading_state() {
+            Ok(libfmod::FMOD_STUDIO_LOADING_STATE::FMOD_STUDIO_LOADING_STATE_UNLOADED) | Err(_) => {
+                if let Err(e) = unsafe { source.release() } {
+                    error.get_or_insert(e);
+                }
+            }
+            Ok(_) => pending.push(UnloadingBank(bank, source)),
+        }
+    }
+
+    UNLOADING_BANKS.lock().unwrap().extend(pending);
+
+    error.map_or(Ok(()), Err)
+}
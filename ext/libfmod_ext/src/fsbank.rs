@@ -0,0 +1,461 @@
+// Copyright (C) 2022 Lily Lyons
+//
+// This file is part of libfmod.
+//
+// libfmod is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// libfmod is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
+
+// FSBank builds FSB sample banks out of audio files.
+// It is one global builder and not an object, so it is bound as the FMOD::FSBank module.
+
+use std::ffi::{c_char, c_uint, CStr, CString};
+
+use magnus::{Class, Module, Object, RHash, RString, RStruct};
+use once_cell::sync::OnceCell;
+
+use crate::enums::{FSBankFormat, FSBankResult, FSBankState};
+use crate::error::{new_exception, ErrorClass};
+use crate::thread::without_gvl_no_ubf;
+
+// Ruby class name of every FMOD::FSBank::Error subclass, by result code.
+const RESULTS: &[(libfmod::FSBANK_RESULT, &str)] = &[
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_CACHE_CHUNKNOTFOUND,
+        "CacheChunkNotFound",
+    ),
+    (libfmod::FSBANK_RESULT::FSBANK_ERR_CANCELLED, "Cancelled"),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_CANNOT_CONTINUE,
+        "CannotContinue",
+    ),
+    (libfmod::FSBANK_RESULT::FSBANK_ERR_ENCODER, "Encoder"),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_ENCODER_INIT,
+        "EncoderInit",
+    ),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_ENCODER_NOTSUPPORTED,
+        "EncoderNotSupported",
+    ),
+    (libfmod::FSBANK_RESULT::FSBANK_ERR_FILE_OS, "FileOs"),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_FILE_NOTFOUND,
+        "FileNotFound",
+    ),
+    (libfmod::FSBANK_RESULT::FSBANK_ERR_FMOD, "Fmod"),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_INITIALIZED,
+        "Initialized",
+    ),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_INVALID_FORMAT,
+        "InvalidFormat",
+    ),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_INVALID_PARAM,
+        "InvalidParam",
+    ),
+    (libfmod::FSBANK_RESULT::FSBANK_ERR_MEMORY, "Memory"),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_UNINITIALIZED,
+        "Uninitialized",
+    ),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_WRITER_FORMAT,
+        "WriterFormat",
+    ),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_WARN_CANNOTLOOP,
+        "WarnCannotLoop",
+    ),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_WARN_IGNORED_FILTERHIGHFREQ,
+        "WarnIgnoredFilterHighFreq",
+    ),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_WARN_IGNORED_DISABLESEEKING,
+        "WarnIgnoredDisableSeeking",
+    ),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_WARN_FORCED_DONTWRITENAMES,
+        "WarnForcedDontWriteNames",
+    ),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_ENCODER_FILE_NOTFOUND,
+        "EncoderFileNotFound",
+    ),
+    (
+        libfmod::FSBANK_RESULT::FSBANK_ERR_ENCODER_FILE_BAD,
+        "EncoderFileBad",
+    ),
+];
+
+// FSBank_ErrorString is a static function in fsbank_errors.h, so bindgen can't see it.
+fn message(result: libfmod::FSBANK_RESULT) -> &'static str {
+    match result {
+        libfmod::FSBANK_RESULT::FSBANK_OK => "No errors.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_CACHE_CHUNKNOTFOUND => "An expected chunk is missing from the cache, perhaps try deleting cache files.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_CANCELLED => "The build process was cancelled during compilation by the user.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_CANNOT_CONTINUE => "The build process cannot continue due to previously ignored errors.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_ENCODER => "Encoder for chosen format has encountered an unexpected error.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_ENCODER_INIT => "Encoder initialization failed.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_ENCODER_NOTSUPPORTED => "Encoder for chosen format is not supported on this platform.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_FILE_OS => "An operating system based file error was encountered.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_FILE_NOTFOUND => "A specified file could not be found.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_FMOD => "Internal error from FMOD sub-system.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_INITIALIZED => "Already initialized.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_INVALID_FORMAT => "The format of the source file is invalid.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_INVALID_PARAM => "An invalid parameter has been passed to this function.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_MEMORY => "Run out of memory.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_UNINITIALIZED => "Not initialized yet.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_WRITER_FORMAT => "Chosen encode format is not supported by this FSB version.",
+        libfmod::FSBANK_RESULT::FSBANK_WARN_CANNOTLOOP => "Source file is too short for seamless looping. Looping disabled.",
+        libfmod::FSBANK_RESULT::FSBANK_WARN_IGNORED_FILTERHIGHFREQ => "FSBANK_BUILD_FILTERHIGHFREQ flag ignored: feature only supported by XMA format.",
+        libfmod::FSBANK_RESULT::FSBANK_WARN_IGNORED_DISABLESEEKING => "FSBANK_BUILD_DISABLESEEKING flag ignored: feature only supported by XMA format.",
+        libfmod::FSBANK_RESULT::FSBANK_WARN_FORCED_DONTWRITENAMES => "FSBANK_BUILD_FSB5_DONTWRITENAMES flag forced: cannot write names when source is from memory.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_ENCODER_FILE_NOTFOUND => "External encoder dynamic library not found.",
+        libfmod::FSBANK_RESULT::FSBANK_ERR_ENCODER_FILE_BAD => "External encoder dynamic library could not be loaded, possibly incorrect binary format, incorrect architecture, file corruption.",
+    }
+}
+
+// The FMOD::FSBank::Error subclasses, in the same order as RESULTS.
+static CLASSES: OnceCell<Vec<ErrorClass>> = OnceCell::new();
+
+// Builds an instance of the FMOD::FSBank::Error subclass for `result`. Same idea as crate::error::fmod_error.
+fn fsbank_error(result: libfmod::FSBANK_RESULT, function: &str) -> magnus::Error {
+    let index = RESULTS.iter().position(|(r, _)| *r == result);
+    let (Some(index), Some(classes)) = (index, CLASSES.get()) else {
+        return magnus::Error::new(
+            magnus::exception::runtime_error(),
+            format!("unknown FSBank result {}", result as std::ffi::c_int),
+        );
+    };
+
+    let message = format!("{function} failed: {}", message(result));
+    new_exception(classes[index], message, |exception| {
+        exception.ivar_set("@code", result as std::ffi::c_int)?;
+        exception.ivar_set("@result", FSBankResult::from(result))?;
+        exception.ivar_set("@function", function)
+    })
+}
+
+fn check(function: &str, result: libfmod::FSBANK_RESULT) -> Result<(), magnus::Error> {
+    match result {
+        libfmod::FSBANK_RESULT::FSBANK_OK => Ok(()),
+        error => Err(fsbank_error(error, function)),
+    }
+}
+
+fn c_string(string: String) -> Result<CString, magnus::Error> {
+    CString::new(string)
+        .map_err(|e| magnus::Error::new(magnus::exception::arg_error(), e.to_string()))
+}
+
+fn init(
+    flags: c_uint,
+    num_simultaneous_jobs: c_uint,
+    cache_directory: Option<String>,
+) -> Result<(), magnus::Error> {
+    let cache_directory = cache_directory.map(c_string).transpose()?;
+
+    unsafe {
+        let result = libfmod::FSBank_Init(
+            libfmod::FSBANK_FSBVERSION::FSBANK_FSBVERSION_FSB5,
+            flags,
+            num_simultaneous_jobs,
+            cache_directory
+                .as_ref()
+                .map_or(std::ptr::null(), |c| c.as_ptr()),
+        );
+
+        check("FSBank_Init", result)
+    }
+}
+
+fn release() -> Result<(), magnus::Error> {
+    unsafe { check("FSBank_Release", libfmod::FSBank_Release()) }
+}
+
+fn cancel() -> Result<(), magnus::Error> {
+    unsafe { check("FSBank_BuildCancel", libfmod::FSBank_BuildCancel()) }
+}
+
+// Owns everything an FSBANK_SUBSOUND points to.
+struct Subsound {
+    file_names: Vec<CString>,
+    file_name_ptrs: Vec<*const c_char>,
+    override_flags: c_uint,
+    override_quality: c_uint,
+    desired_sample_rate: f32,
+    percent_optimized_rate: f32,
+}
+
+impl Subsound {
+    // A subsound is a file name, an array of file names (interleaved into one multichannel subsound),
+    // or a hash of `files:` and any of `override_flags:`, `override_quality:`, `desired_sample_rate:` and `percent_optimized_rate:`.
+    fn from_value(value: magnus::Value) -> Result<Self, magnus::Error> {
+        let options = RHash::from_value(value);
+        let files = match options {
+            Some(options) => options.fetch(magnus::Symbol::new("files"))?,
+            None => value,
+        };
+
+        let file_names: Vec<String> = match RString::from_value(files) {
+            Some(file_name) => vec![file_name.to_string()?],
+            None => files.try_convert()?,
+        };
+        let file_names = file_names
+            .into_iter()
+            .map(c_string)
+            .collect::<Result<Vec<_>, _>>()?;
+        let file_name_ptrs = file_names.iter().map(|f| f.as_ptr()).collect();
+
+        let option = |name: &str| -> Result<Option<magnus::Value>, magnus::Error> {
+            match options {
+                Some(options) => options.lookup(magnus::Symbol::new(name)),
+                None => Ok(None),
+            }
+        };
+
+        Ok(Self {
+            file_names,
+            file_name_ptrs,
+            override_flags: option("override_flags")?
+                .map(|v| v.try_convert())
+                .transpose()?
+                .unwrap_or(0),
+            override_quality: option("override_quality")?
+                .map(|v| v.try_convert())
+                .transpose()?
+                .unwrap_or(0),
+            desired_sample_rate: option("desired_sample_rate")?
+                .map(|v| v.try_convert())
+                .transpose()?
+                .unwrap_or(0.0),
+            percent_optimized_rate: option("percent_optimized_rate")?
+                .map(|v| v.try_convert())
+                .transpose()?
+                .unwrap_or(0.0),
+        })
+    }
+
+    fn as_fsbank(&self) -> libfmod::FSBANK_SUBSOUND {
+        libfmod::FSBANK_SUBSOUND {
+            fileNames: self.file_name_ptrs.as_ptr(),
+            fileData: std::ptr::null(),
+            fileDataLengths: std::ptr::null(),
+            numFiles: self.file_names.len() as c_uint,
+            overrideFlags: self.override_flags,
+            overrideQuality: self.override_quality,
+            desiredSampleRate: self.desired_sample_rate,
+            percentOptimizedRate: self.percent_optimized_rate,
+        }
+    }
+}
+
+// build(subsounds, format:, flags: 0, quality: 0, encrypt_key: nil, output: nil)
+// Without an output file name the FSB is built in memory and returned as a binary String.
+fn build(args: &[magnus::Value]) -> Result<Option<RString>, magnus::Error> {
+    use magnus::scan_args::{get_kwargs, scan_args};
+
+    let args = scan_args::<(magnus::RArray,), (), (), (), RHash, ()>(args)?;
+    let (subsounds,) = args.required;
+    let kwargs = get_kwargs::<
+        _,
+        (&FSBankFormat,),
+        (
+            Option<c_uint>,
+            Option<c_uint>,
+            Option<String>,
+            Option<String>,
+        ),
+        (),
+    >(
+        args.keywords,
+        &["format"],
+        &["flags", "quality", "encrypt_key", "output"],
+    )?;
+    let (format,) = kwargs.required;
+    let (flags, quality, encrypt_key, output) = kwargs.optional;
+
+    let subsounds = subsounds
+        .each()
+        .map(|s| s.and_then(Subsound::from_value))
+        .collect::<Result<Vec<_>, _>>()?;
+    let fsbank_subsounds: Vec<_> = subsounds.iter().map(Subsound::as_fsbank).collect();
+
+    let format = libfmod::FSBANK_FORMAT::from(*format);
+    let encrypt_key = encrypt_key.map(c_string).transpose()?;
+    let output = output.map(c_string).transpose()?;
+    let in_memory = output.is_none();
+
+    unsafe {
+        // Encoding takes a while. Releasing the GVL lets other threads poll FSBank.next_progress_item meanwhile.
+        let result = without_gvl_no_ubf(|| {
+            libfmod::FSBank_Build(
+                fsbank_subsounds.as_ptr(),
+                fsbank_subsounds.len() as c_uint,
+                format,
+                flags.unwrap_or(0),
+                quality.unwrap_or(0),
+                encrypt_key
+                    .as_ref()
+                    .map_or(std::ptr::null(), |k| k.as_ptr()),
+                output.as_ref().map_or(std::ptr::null(), |o| o.as_ptr()),
+            )
+        });
+        check("FSBank_Build", result)?;
+
+        if !in_memory {
+            return Ok(None);
+        }
+
+        let mut data = std::ptr::null();
+        let mut length = 0;
+        check(
+            "FSBank_FetchFSBMemory",
+            libfmod::FSBank_FetchFSBMemory(&mut data, &mut length),
+        )?;
+
+        Ok(Some(RString::from_slice(std::slice::from_raw_parts(
+            data as *const u8,
+            length as usize,
+        ))))
+    }
+}
+
+// Progress items are only generated when FSBank is initialized with FSBANK_INIT_GENERATEPROGRESSITEMS.
+fn next_progress_item() -> Result<Option<RStruct>, magnus::Error> {
+    use crate::wrap::WrapFMOD;
+
+    unsafe {
+        let mut item = std::ptr::null();
+        check(
+            "FSBank_FetchNextProgressItem",
+            libfmod::FSBank_FetchNextProgressItem(&mut item),
+        )?;
+
+        let Some(progress) = item.as_ref() else {
+            return Ok(None);
+        };
+
+        // Failures and warnings come with a description of what went wrong.
+        let message = match progress.state {
+            libfmod::FSBANK_STATE::FSBANK_STATE_FAILED => {
+                let data = &*(progress.stateData as *const libfmod::FSBANK_STATEDATA_FAILED);
+                Some(CStr::from_ptr(data.errorString.as_ptr()))
+            }
+            libfmod::FSBANK_STATE::FSBANK_STATE_WARNING => {
+                let data = &*(progress.stateData as *const libfmod::FSBANK_STATEDATA_WARNING);
+                Some(CStr::from_ptr(data.warningString.as_ptr()))
+            }
+            _ => None,
+        }
+        .map(|m| m.to_string_lossy().into_owned());
+
+        let class = magnus::class::object()
+            .const_get::<_, magnus::RModule>("FMOD")?
+            .const_get::<_, magnus::RModule>("FSBank")?
+            .const_get::<_, magnus::RClass>("ProgressItem")?;
+        let state: FSBankState = progress.state.wrap_fmod();
+        let rstruct =
+            class.new_instance((progress.subSoundIndex, progress.threadIndex, state, message));
+
+        check(
+            "FSBank_ReleaseProgressItem",
+            libfmod::FSBank_ReleaseProgressItem(item),
+        )?;
+
+        Ok(Some(RStruct::from_value(rstruct?).unwrap()))
+    }
+}
+
+pub fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    let fsbank = module.define_module("FSBank")?;
+
+    let error = fsbank.define_error("Error", magnus::exception::standard_error())?;
+    // code and result are defined in lib/libfmod/error.rb.
+    let _: magnus::Value = error.funcall("attr_reader", (magnus::Symbol::new("function"),))?;
+    let classes = RESULTS
+        .iter()
+        .map(|&(result, name)| {
+            let class = error.define_error(name, error)?;
+            class.const_set("CODE", result as std::ffi::c_int)?;
+
+            Ok(ErrorClass(class))
+        })
+        .collect::<Result<Vec<_>, magnus::Error>>()?;
+    let _ = CLASSES.set(classes);
+
+    fsbank.const_set(
+        "ProgressItem",
+        magnus::r_struct::define_struct(
+            Some("ProgressItem"),
+            ("subsound_index", "thread_index", "state", "message"),
+        )?,
+    )?;
+
+    let init_flags = fsbank.define_module("InitFlags")?;
+    for (name, flag) in [
+        ("NORMAL", libfmod::FSBANK_INIT_NORMAL),
+        ("IGNOREERRORS", libfmod::FSBANK_INIT_IGNOREERRORS),
+        ("WARNINGSASERRORS", libfmod::FSBANK_INIT_WARNINGSASERRORS),
+        (
+            "CREATEINCLUDEHEADER",
+            libfmod::FSBANK_INIT_CREATEINCLUDEHEADER,
+        ),
+        (
+            "DONTLOADCACHEFILES",
+            libfmod::FSBANK_INIT_DONTLOADCACHEFILES,
+        ),
+        (
+            "GENERATEPROGRESSITEMS",
+            libfmod::FSBANK_INIT_GENERATEPROGRESSITEMS,
+        ),
+    ] {
+        init_flags.const_set(name, flag)?;
+    }
+
+    let build_flags = fsbank.define_module("BuildFlags")?;
+    for (name, flag) in [
+        ("DEFAULT", libfmod::FSBANK_BUILD_DEFAULT),
+        ("DISABLESYNCPOINTS", libfmod::FSBANK_BUILD_DISABLESYNCPOINTS),
+        ("DONTLOOP", libfmod::FSBANK_BUILD_DONTLOOP),
+        ("FILTERHIGHFREQ", libfmod::FSBANK_BUILD_FILTERHIGHFREQ),
+        ("DISABLESEEKING", libfmod::FSBANK_BUILD_DISABLESEEKING),
+        (
+            "OPTIMIZESAMPLERATE",
+            libfmod::FSBANK_BUILD_OPTIMIZESAMPLERATE,
+        ),
+        (
+            "FSB5_DONTWRITENAMES",
+            libfmod::FSBANK_BUILD_FSB5_DONTWRITENAMES,
+        ),
+        ("NOGUID", libfmod::FSBANK_BUILD_NOGUID),
+        ("WRITEPEAKVOLUME", libfmod::FSBANK_BUILD_WRITEPEAKVOLUME),
+        ("ALIGN4K", libfmod::FSBANK_BUILD_ALIGN4K),
+    ] {
+        build_flags.const_set(name, flag)?;
+    }
+
+    fsbank.define_module_function("init", magnus::function!(init, 3))?;
+    fsbank.define_module_function("release", magnus::function!(release, 0))?;
+    fsbank.define_module_function("build", magnus::function!(build, -1))?;
+    fsbank.define_module_function("cancel", magnus::function!(cancel, 0))?;
+    fsbank.define_module_function(
+        "next_progress_item",
+        magnus::function!(next_progress_item, 0),
+    )?;
+
+    Ok(())
+}
@@ -1,11 +1,14 @@
 #![warn(rust_2018_idioms, clippy::all)]
 #![feature(macro_metavar_expr)]
 
-use magnus::{rb_sys::FromRawValue, Module};
-use thread::spawn_rb_thread;
+use magnus::Module;
 
 mod callback;
 mod enums;
+mod error;
+mod file;
+#[cfg(fsbank)]
+mod fsbank;
 mod thread;
 mod transparent_struct;
 mod wrap;
@@ -20,6 +23,11 @@ mod studio {
 }
 
 mod core {
+    pub mod channel;
+    pub mod channel_control;
+    pub mod channel_group;
+    pub mod dsp;
+    pub mod sound;
     pub mod system;
 }
 
@@ -50,6 +58,7 @@ fn init() -> Result<(), magnus::Error> {
     }
 
     let top = magnus::define_module("FMOD")?;
+    error::bind(top)?;
 
     let core = top.define_module("Core")?;
     let studio = top.define_module("Studio")?;
@@ -58,6 +67,8 @@ fn init() -> Result<(), magnus::Error> {
 
     enums::bind_enums(enums)?;
     transparent_struct::bind(top)?;
+    #[cfg(fsbank)]
+    fsbank::bind(top)?;
 
     studio::bank::bind(studio)?;
     studio::bus::bind(studio)?;
@@ -66,13 +77,13 @@ fn init() -> Result<(), magnus::Error> {
     studio::system::bind_system(studio)?;
     studio::vca::bind(studio)?;
 
+    core::channel::bind(core)?;
+    core::channel_group::bind(core)?;
+    core::dsp::bind(core)?;
+    core::sound::bind(core)?;
     core::system::bind(core)?;
 
-    unsafe {
-        let callback_thread = magnus::Value::from_raw(spawn_rb_thread(callback::callback_thread));
-
-        top.const_set("EventThread", callback_thread)?;
-    }
+    callback::bind(top)?;
 
     Ok(())
 }
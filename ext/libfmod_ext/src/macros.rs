@@ -37,7 +37,7 @@ macro_rules! bindable_enum {
         }
 
         impl $name {
-            fn new(e: std::ffi::c_int) -> Result<Self, magnus::Error> {
+            fn new(e: std::ffi::c_int) -> std::result::Result<Self, magnus::Error> {
                 let error = Err(magnus::Error::new(magnus::exception::arg_error(), format!("invalid variant {e}")));
                 $(
                     if libfmod::$c_name::$c_force_int as std::ffi::c_int == e {
@@ -62,7 +62,18 @@ macro_rules! bindable_enum {
                 format!("FMOD::Enum::{}::{:#?}", stringify!($name), self.0)
             }
 
-            fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
+            // Variants hash and compare by their C value, so they work as Hash keys.
+            fn rb_hash(&self) -> std::ffi::c_int {
+                self.0 as std::ffi::c_int
+            }
+
+            fn rb_eql(&self, other: magnus::Value) -> bool {
+                other
+                    .try_convert::<&Self>()
+                    .map_or(false, |other| self.0 as std::ffi::c_int == other.0 as std::ffi::c_int)
+            }
+
+            fn bind(module: impl magnus::Module) -> std::result::Result<(), magnus::Error> {
                 use magnus::Object;
                 use magnus::Module;
 
@@ -75,6 +86,8 @@ macro_rules! bindable_enum {
                 class.define_method("to_s", magnus::method!($name::rb_to_string, 0))?;
                 class.define_method("inspect", magnus::method!($name::rb_to_string, 0))?;
                 class.define_method("==", magnus::method!($name::eq, 1))?;
+                class.define_method("eql?", magnus::method!($name::rb_eql, 1))?;
+                class.define_method("hash", magnus::method!($name::rb_hash, 0))?;
                 class.define_method("to_i", magnus::method!($name::rb_to_i, 0))?;
                 class.define_singleton_method("new", magnus::function!($name::new, 1))?;
 
@@ -93,6 +106,19 @@ macro_rules! bindable_enum {
                 self.0
             }
         }
+
+        impl $crate::wrap::TryUnwrapFMOD<libfmod::$c_name> for $name {
+            fn try_unwrap_fmod(self) -> Result<libfmod::$c_name, magnus::Error> {
+                Ok(self.0)
+            }
+        }
+
+        // Struct fields holding an enum are read as references.
+        impl $crate::wrap::TryUnwrapFMOD<libfmod::$c_name> for &$name {
+            fn try_unwrap_fmod(self) -> Result<libfmod::$c_name, magnus::Error> {
+                Ok(self.0)
+            }
+        }
     };
 }
 
@@ -111,6 +137,21 @@ macro_rules! opaque_struct {
             }
         }
 
+        impl $name {
+            // Handles are equal when they point to the same FMOD object, so they hash by that pointer.
+            // This lets them be used as Hash keys and with Array#uniq.
+            fn rb_hash(&self) -> usize {
+                self.0.as_mut_ptr() as usize
+            }
+
+            // Unlike ==, eql? is called by Hash with whatever other key happens to collide.
+            fn rb_eql(&self, other: magnus::Value) -> bool {
+                other
+                    .try_convert::<&Self>()
+                    .map_or(false, |other| self == other)
+            }
+        }
+
         impl $crate::wrap::WrapFMOD<$name> for libfmod::$name {
             fn wrap_fmod(self) -> $name {
                 $name(self)
@@ -123,6 +164,25 @@ macro_rules! opaque_struct {
             }
         }
 
+        // Needed for optional arguments (Option<&T>).
+        impl $crate::wrap::UnwrapFMOD<libfmod::$name> for &$name {
+            fn unwrap_fmod(self) -> libfmod::$name {
+                self.0
+            }
+        }
+
+        impl $crate::wrap::TryUnwrapFMOD<libfmod::$name> for $name {
+            fn try_unwrap_fmod(self) -> Result<libfmod::$name, magnus::Error> {
+                Ok(self.0)
+            }
+        }
+
+        impl $crate::wrap::TryUnwrapFMOD<libfmod::$name> for &$name {
+            fn try_unwrap_fmod(self) -> Result<libfmod::$name, magnus::Error> {
+                Ok(self.0)
+            }
+        }
+
         unsafe impl Send for $name {}
     };
 }
@@ -137,9 +197,43 @@ macro_rules! opaque_struct_method {
                 $( [<arg_ ${index()}>]: $arg, )*
             ) $( -> Result<$result, magnus::Error> )? {
                 use $crate::wrap::WrapFMOD;
-                use $crate::wrap::UnwrapFMOD;
+                use $crate::wrap::TryUnwrapFMOD;
+
+                self.0.$fn_name($( $( ${ignore(ref)} &)?[<arg_ ${index()}>].try_unwrap_fmod()?, ${ignore(arg)} )*).wrap_fmod()
+            }
+        }
+    };
+}
+
+// libfmod-gen does NOT generate the functions that fill an array correctly, so we write them with this instead.
+// The array is sized with $count_fn, and each element is converted with $convert before being wrapped.
+#[macro_export]
+macro_rules! opaque_struct_list {
+    ($fn_name:ident, $result:ty; $count_fn:ident, $c_fn:ident, $convert:expr) => {
+        fn $fn_name(&self) -> Result<Vec<$result>, magnus::Error> {
+            unsafe {
+                use $crate::wrap::WrapFMOD;
+
+                let mut array = Vec::with_capacity(1.max(self.$count_fn()? as usize));
+                let mut count = 0;
+
+                let result = libfmod::ffi::$c_fn(
+                    self.0.as_mut_ptr(),
+                    array.as_mut_ptr(),
+                    array.capacity() as i32,
+                    &mut count as *mut _,
+                );
+                //? SAFETY:
+                //? FMOD ensures that count <= capacity.
+                array.set_len(count as _);
 
-                self.0.$fn_name($( $( ${ignore(ref)} &)?[<arg_ ${index()}>].unwrap_fmod(), ${ignore(arg)} )*).wrap_fmod()
+                match result {
+                    libfmod::ffi::FMOD_OK => Ok(array
+                        .into_iter()
+                        .map(|e| ($convert)(e).wrap_fmod())
+                        .collect()),
+                    error => Err($crate::err_fmod!(stringify!($c_fn), error)),
+                }
             }
         }
     };
@@ -175,6 +269,8 @@ macro_rules! bind_fn {
                 }
             )*
             class.define_method("==", magnus::method!($name::eq, 1))?;
+            class.define_method("eql?", magnus::method!($name::rb_eql, 1))?;
+            class.define_method("hash", magnus::method!($name::rb_hash, 0))?;
 
             Ok(())
         }
@@ -184,13 +280,19 @@ macro_rules! bind_fn {
 #[macro_export]
 macro_rules! transparent_struct {
     ($name:ident, $c_name:ident; [$($c_member:ident, $member:ident: $type:ty),*]) => {
-        impl $crate::wrap::UnwrapFMOD<libfmod::$c_name> for magnus::RStruct {
-            fn unwrap_fmod(self) -> libfmod::$c_name {
-                libfmod::$c_name {
+        impl $crate::wrap::TryUnwrapFMOD<libfmod::$c_name> for magnus::RStruct {
+            fn try_unwrap_fmod(self) -> Result<libfmod::$c_name, magnus::Error> {
+                #[allow(unused_imports)]
+                use $crate::wrap::TryUnwrapFMOD;
+
+                Ok(libfmod::$c_name {
                     $(
-                        $c_member: self.aref::<_, $type>(stringify!($member)).unwrap().unwrap_fmod(),
+                        $c_member: self
+                            .aref::<_, $type>(stringify!($member))
+                            .and_then(TryUnwrapFMOD::try_unwrap_fmod)
+                            .map_err($crate::wrap::field_type_error(stringify!($name), stringify!($member)))?,
                     )*
-                }
+                })
             }
         }
 
@@ -230,3 +332,10 @@ macro_rules! transparent_struct {
 
     };
 }
+
+#[macro_export]
+macro_rules! err_fmod {
+    ($function:expr, $result:expr) => {
+        $crate::error::fmod_error($result as std::ffi::c_int, Some($function))
+    };
+}
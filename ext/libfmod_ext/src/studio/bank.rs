@@ -16,17 +16,54 @@
 // along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
 
 #[allow(unused_imports)]
-use crate::{bind_fn, opaque_struct, opaque_struct_function, opaque_struct_method};
+use crate::{
+    bind_fn, opaque_struct, opaque_struct_function, opaque_struct_list, opaque_struct_method,
+};
 use crate::{
     enums::LoadingState,
     err_fmod,
-    studio::{event::EventDescription, vca::Vca},
+    file::BankSource,
+    studio::{
+        bus::Bus,
+        event::{EventDescription, EventUserData},
+        vca::Vca,
+    },
+    thread::without_gvl_no_ubf,
 };
 
 opaque_struct!(Bank, "Studio", "Bank");
 
-/// FIXME: Add functions with capacity.
-/// libfmod-gen does NOT generate them correctly.
+// User data taken off a bank and its event descriptions while it is unloaded (see Bank#detach_user_data).
+pub(crate) struct DetachedUserData {
+    bank: libfmod::Bank,
+    descriptions: Vec<(EventDescription, *mut EventUserData)>,
+    source: *mut BankSource,
+}
+
+impl DetachedUserData {
+    // Must be called on a Ruby thread, once FMOD has been told to unload the bank.
+    pub(crate) fn release(self) {
+        unsafe {
+            for (_, user_data) in self.descriptions {
+                EventUserData::release(user_data);
+            }
+
+            if !self.source.is_null() {
+                crate::file::release_when_unloaded(self.bank, *Box::from_raw(self.source));
+            }
+        }
+    }
+
+    pub(crate) fn reattach(self) {
+        unsafe {
+            for (description, user_data) in self.descriptions {
+                description.reattach_user_data(user_data);
+            }
+
+            libfmod::ffi::FMOD_Studio_Bank_SetUserData(self.bank.as_mut_ptr(), self.source as _);
+        }
+    }
+}
 
 impl Bank {
     fn is_valid(&self) -> bool {
@@ -66,9 +103,63 @@ impl Bank {
         }
     }
 
-    opaque_struct_method!(unload, (););
+    // Unloading a bank destroys its event descriptions, so their user data is taken off them beforehand.
+    // It's only freed once FMOD has accepted the unload, and put back if it didn't.
+    fn unload(&self) -> Result<(), magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let detached = self.detach_user_data()?;
+
+        match self.0.unload() {
+            Ok(()) => {
+                detached.release();
+                Ok(())
+            }
+            Err(e) => {
+                detached.reattach();
+                Err(e.wrap_fmod())
+            }
+        }
+    }
+
+    pub(crate) fn detach_user_data(&self) -> Result<DetachedUserData, magnus::Error> {
+        // A bank that is still loading (or failed to) has no event descriptions we could have given user data.
+        let descriptions = match self.0.get_loading_state() {
+            Ok(libfmod::FMOD_STUDIO_LOADING_STATE::FMOD_STUDIO_LOADING_STATE_LOADED) => self
+                .get_event_list()?
+                .into_iter()
+                .map(|description| (description, unsafe { description.detach_user_data() }))
+                .filter(|(_, user_data)| !user_data.is_null())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        // Banks loaded with Studio::System#load_bank_custom (or from pointed to memory) hold on to what they were loaded from.
+        let source = unsafe {
+            let mut source = std::ptr::null_mut();
+            libfmod::ffi::FMOD_Studio_Bank_GetUserData(self.0.as_mut_ptr(), &mut source);
+            libfmod::ffi::FMOD_Studio_Bank_SetUserData(self.0.as_mut_ptr(), std::ptr::null_mut());
+
+            source as *mut BankSource
+        };
+
+        Ok(DetachedUserData {
+            bank: self.0,
+            descriptions,
+            source,
+        })
+    }
+
     opaque_struct_method!(load_sample_data, (););
-    opaque_struct_method!(unload_sample_data, (););
+
+    // Unloading waits for sample data that is still loading, which may need Ruby to read it (see file.rs).
+    fn unload_sample_data(&self) -> Result<(), magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let bank = self.0;
+        unsafe { without_gvl_no_ubf(|| bank.unload_sample_data()) }.map_err(|e| e.wrap_fmod())
+    }
+
     opaque_struct_method!(get_loading_state, LoadingState;);
     opaque_struct_method!(get_sample_loading_state, LoadingState;);
     opaque_struct_method!(get_string_count, i32;);
@@ -114,63 +205,20 @@ impl Bank {
     }
 
     opaque_struct_method!(get_event_count, i32;);
-
-    fn get_event_list(&self) -> Result<Vec<EventDescription>, magnus::Error> {
-        unsafe {
-            use crate::wrap::WrapFMOD;
-
-            let mut array = Vec::with_capacity(1.max(self.get_event_count()? as usize));
-            let mut count = 0;
-
-            let result = libfmod::ffi::FMOD_Studio_Bank_GetEventList(
-                self.0.as_mut_ptr(),
-                array.as_mut_ptr(),
-                array.capacity() as i32,
-                &mut count as *mut _,
-            );
-            //? SAFETY:
-            //? FMOD ensures that count <= capacity.
-            array.set_len(count as _);
-
-            match result {
-                libfmod::ffi::FMOD_OK => Ok(array
-                    .into_iter()
-                    .map(|e| libfmod::EventDescription::from(e).wrap_fmod())
-                    .collect()),
-                error => Err(err_fmod!("FMOD_Studio_Bank_GetEventList", error)),
-            }
-        }
-    }
-
+    opaque_struct_list!(
+        get_event_list, EventDescription;
+        get_event_count, FMOD_Studio_Bank_GetEventList, libfmod::EventDescription::from
+    );
     opaque_struct_method!(get_bus_count, i32;);
+    opaque_struct_list!(
+        get_bus_list, Bus;
+        get_bus_count, FMOD_Studio_Bank_GetBusList, libfmod::Bus::from
+    );
     opaque_struct_method!(get_vca_count, i32;);
-
-    fn get_vca_list(&self) -> Result<Vec<Vca>, magnus::Error> {
-        unsafe {
-            use crate::wrap::WrapFMOD;
-
-            let mut array = Vec::with_capacity(1.max(self.get_event_count()? as usize));
-            let mut count = 0;
-
-            let result = libfmod::ffi::FMOD_Studio_Bank_GetVCAList(
-                self.0.as_mut_ptr(),
-                array.as_mut_ptr(),
-                array.capacity() as i32,
-                &mut count as *mut _,
-            );
-            //? SAFETY:
-            //? FMOD ensures that count <= capacity.
-            array.set_len(count as _);
-
-            match result {
-                libfmod::ffi::FMOD_OK => Ok(array
-                    .into_iter()
-                    .map(|e| libfmod::Vca::from(e).wrap_fmod())
-                    .collect()),
-                error => Err(err_fmod!("FMOD_Studio_Bank_GetVCAList", error)),
-            }
-        }
-    }
+    opaque_struct_list!(
+        get_vca_list, Vca;
+        get_vca_count, FMOD_Studio_Bank_GetVCAList, libfmod::Vca::from
+    );
 
     bind_fn! {
         Bank, "Bank";
@@ -187,6 +235,7 @@ impl Bank {
         (get_event_count, method, 0),
         (get_event_list, method, 0),
         (get_bus_count, method, 0),
+        (get_bus_list, method, 0),
         (get_vca_count, method, 0),
         (get_vca_list, method, 0)
     }
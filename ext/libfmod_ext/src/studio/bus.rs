@@ -17,7 +17,7 @@
 
 #[allow(unused_imports)]
 use crate::{bind_fn, opaque_struct, opaque_struct_function, opaque_struct_method};
-use crate::{enums::StopMode, err_fmod};
+use crate::{core::channel_group::ChannelGroup, enums::StopMode, err_fmod};
 
 opaque_struct!(Bus, "Studio", "Bus");
 
@@ -70,8 +70,8 @@ impl Bus {
     opaque_struct_method!(set_port_index, (); (u64));
     opaque_struct_method!(lock_channel_group, (););
     opaque_struct_method!(unlock_channel_group, (););
-
-    // TODO: ChannelGroup
+    // The channel group only exists while the bus is locked or has events playing on it.
+    opaque_struct_method!(get_channel_group, ChannelGroup;);
 
     opaque_struct_method!(get_cpu_usage, (u32, u32););
     opaque_struct_method!(get_memory_usage, magnus::RStruct;);
@@ -92,6 +92,7 @@ impl Bus {
         (set_port_index, method, 1),
         (lock_channel_group, method, 0),
         (unlock_channel_group, method, 0),
+        (get_channel_group, method, 0),
         (get_cpu_usage, method, 0),
         (get_memory_usage, method, 0)
     }
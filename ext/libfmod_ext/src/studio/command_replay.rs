@@ -70,7 +70,7 @@ unsafe extern "C" fn create_instance_callback(
     );
 
     reciever.recv().unwrap_or_else(|e| {
-        println!("Warning callback recv error: {e}");
+        eprintln!("Warning callback recv error: {e}");
         0
     })
 }
@@ -91,7 +91,7 @@ unsafe extern "C" fn frame_callback(
     );
 
     reciever.recv().unwrap_or_else(|e| {
-        println!("Warning callback recv error: {e}");
+        eprintln!("Warning callback recv error: {e}");
         0
     })
 }
@@ -125,7 +125,7 @@ unsafe extern "C" fn load_bank_callback(
     );
 
     reciever.recv().unwrap_or_else(|e| {
-        println!("Warning callback recv error: {e}");
+        eprintln!("Warning callback recv error: {e}");
         0
     })
 }
@@ -189,7 +189,20 @@ impl CommandReplay {
     opaque_struct_method!(set_paused, (); (bool));
     opaque_struct_method!(get_playback_state, PlaybackState;);
     opaque_struct_method!(get_current_command, (i32, f32););
-    opaque_struct_method!(release, (););
+    fn release(&self) -> Result<(), magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let ptr = self.0.get_user_data().map_err(|e| e.wrap_fmod())? as *mut CommandUserData;
+
+        self.0.release().wrap_fmod()?;
+
+        // The replay is gone, so none of its callbacks can be called anymore.
+        if !ptr.is_null() {
+            unsafe { drop(Box::from_raw(ptr)) }
+        }
+
+        Ok(())
+    }
 
     fn get_user_data(&self) -> Result<Option<magnus::Value>, magnus::Error> {
         self.get_or_create_user_data()
@@ -15,31 +15,65 @@
 // You should have received a copy of the GNU General Public License
 // along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use magnus::{value::BoxValue, RStruct};
 
 #[allow(unused_imports)]
-use crate::{bind_fn, opaque_struct, opaque_struct_function, opaque_struct_method};
 use crate::{
-    callback::EventCallback,
+    bind_fn, opaque_struct, opaque_struct_function, opaque_struct_list, opaque_struct_method,
+};
+use crate::{
+    callback::{EventCallback, UserDataRelease},
+    core::{channel_group::ChannelGroup, sound::Sound},
     enums::{EventProperty, LoadingState, PlaybackState, StopMode},
     err_fmod,
+    thread::without_gvl_no_ubf,
 };
 
 #[derive(Default)]
 pub struct EventUserData {
     pub callback: Option<BoxValue<magnus::Value>>,
     pub userdata: Option<BoxValue<magnus::Value>>,
+    // The callback mask Ruby asked for.
+    // We always listen for FMOD_STUDIO_EVENT_CALLBACK_DESTROYED to free user data, but only pass it on if it is in here.
+    pub mask: std::ffi::c_uint,
+    // Set by `set_callback(..., async: true)`. FMOD doesn't wait on these callbacks.
+    pub deferred: bool,
+    // How many deferred callbacks are queued or running, and whether the user data has been released (DEFERRED_RELEASED).
+    // Whichever of the release or the last deferred callback comes last frees the user data.
+    pub in_flight: AtomicUsize,
+}
+
+const DEFERRED_RELEASED: usize = 1 << (usize::BITS - 1);
+
+impl EventUserData {
+    // Called once FMOD can no longer reach the user data.
+    pub(crate) unsafe fn release(ptr: *mut Self) {
+        if (*ptr)
+            .in_flight
+            .fetch_or(DEFERRED_RELEASED, Ordering::AcqRel)
+            == 0
+        {
+            UserDataRelease::create(Box::from_raw(ptr));
+        }
+    }
+
+    // Called on the Ruby thread after a deferred callback has run.
+    pub(crate) unsafe fn finish_deferred(ptr: *mut Self) {
+        if (*ptr).in_flight.fetch_sub(1, Ordering::AcqRel) == DEFERRED_RELEASED | 1 {
+            drop(Box::from_raw(ptr));
+        }
+    }
 }
 
 pub enum EventCallbackParameterType {
-    // TODO: This needs FMOD_SOUND but we do not have core bindings yet.
-    // ProgrammerSound(libfmod::ProgrammerSoundProperties),
-    // TODO: This needs FMOD_DSP but again we do not have that yet.
-    // PluginProperties(libfmod::PluginInstanceProperties)
+    ProgrammerSound(*mut libfmod::ffi::FMOD_STUDIO_PROGRAMMER_SOUND_PROPERTIES),
+    PluginProperties(libfmod::FMOD_STUDIO_PLUGIN_INSTANCE_PROPERTIES),
     TimelineMarker(libfmod::TimelineMarkerProperties),
     TimelineBeat(libfmod::TimelineBeatProperties),
-    // TODO: This needs FMOD_SOUND.
-    // Sound(libfmod::Sound),
+    Sound(libfmod::Sound),
     EventInstance(libfmod::EventInstance),
     TimelineNested(libfmod::TimelineNestedBeatProperties),
     None,
@@ -52,31 +86,156 @@ impl crate::wrap::WrapFMOD<magnus::Value> for EventCallbackParameterType {
             Self::TimelineBeat(m) => *m.wrap_fmod(),
             Self::TimelineMarker(m) => *m.wrap_fmod(),
             Self::EventInstance(e) => magnus::Value::from(e.wrap_fmod()),
+            Self::PluginProperties(p) => *p.wrap_fmod(),
+            Self::Sound(s) => magnus::Value::from(s.wrap_fmod()),
+            Self::ProgrammerSound(p) => magnus::Value::from(ProgrammerSoundProperties::new(p)),
             Self::None => *magnus::QNIL,
         }
     }
 }
 
+impl EventCallbackParameterType {
+    // Whether the parameter is still safe to use after FMOD's callback has returned.
+    // Programmer sound properties, plugin DSPs and played sounds may be gone by then.
+    fn can_defer(&self) -> bool {
+        !matches!(
+            self,
+            Self::ProgrammerSound(_) | Self::PluginProperties(_) | Self::Sound(_)
+        )
+    }
+}
+
+// Passed to CREATE_PROGRAMMER_SOUND and DESTROY_PROGRAMMER_SOUND callbacks.
+// This points straight at the properties FMOD gave us, so assigning a sound here is what FMOD will play.
+// The properties only live as long as the callback, so they are invalidated once it returns.
+#[magnus::wrap(
+    class = "FMOD::Studio::ProgrammerSoundProperties",
+    free_immediatly,
+    size
+)]
+pub(crate) struct ProgrammerSoundProperties(
+    Cell<*mut libfmod::ffi::FMOD_STUDIO_PROGRAMMER_SOUND_PROPERTIES>,
+);
+
+// This is only ever used from the Ruby thread running the callback.
+unsafe impl Send for ProgrammerSoundProperties {}
+
+impl ProgrammerSoundProperties {
+    fn new(properties: *mut libfmod::ffi::FMOD_STUDIO_PROGRAMMER_SOUND_PROPERTIES) -> Self {
+        Self(Cell::new(properties))
+    }
+
+    pub(crate) fn invalidate(&self) {
+        self.0.set(std::ptr::null_mut());
+    }
+
+    fn get(
+        &self,
+    ) -> Result<&mut libfmod::ffi::FMOD_STUDIO_PROGRAMMER_SOUND_PROPERTIES, magnus::Error> {
+        unsafe { self.0.get().as_mut() }.ok_or_else(|| {
+            magnus::Error::new(
+                magnus::exception::runtime_error(),
+                "programmer sound properties can only be used inside of their callback",
+            )
+        })
+    }
+
+    fn name(&self) -> Result<String, magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        Ok(self.get()?.name.wrap_fmod())
+    }
+
+    fn sound(&self) -> Result<Option<Sound>, magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let sound = self.get()?.sound;
+        Ok((!sound.is_null()).then(|| libfmod::Sound::from(sound).wrap_fmod()))
+    }
+
+    fn set_sound(&self, sound: Option<&Sound>) -> Result<(), magnus::Error> {
+        use crate::wrap::UnwrapFMOD;
+
+        self.get()?.sound = sound.map_or(std::ptr::null_mut(), |s| {
+            UnwrapFMOD::<libfmod::Sound>::unwrap_fmod(s).as_mut_ptr()
+        });
+
+        Ok(())
+    }
+
+    fn subsound_index(&self) -> Result<i32, magnus::Error> {
+        Ok(self.get()?.subsoundIndex)
+    }
+
+    fn set_subsound_index(&self, index: i32) -> Result<(), magnus::Error> {
+        self.get()?.subsoundIndex = index;
+
+        Ok(())
+    }
+
+    fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
+        use magnus::{method, Module, Object};
+
+        let class = module.define_class("ProgrammerSoundProperties", Default::default())?;
+        class.define_method("name", method!(Self::name, 0))?;
+        class.define_method("sound", method!(Self::sound, 0))?;
+        class.define_method("sound=", method!(Self::set_sound, 1))?;
+        class.define_method("subsound_index", method!(Self::subsound_index, 0))?;
+        class.define_method("subsound_index=", method!(Self::set_subsound_index, 1))?;
+
+        Ok(())
+    }
+}
+
 unsafe extern "C" fn event_callback(
     type_: u32,
     instance: *mut libfmod::ffi::FMOD_STUDIO_EVENTINSTANCE,
     data: *mut std::ffi::c_void,
 ) -> i32 {
+    // This is called from FMOD's threads, and unwinding into FMOD would abort the whole process.
+    // So anything that goes wrong is printed to stderr, and FMOD is told everything is fine.
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        dispatch_event_callback(type_, instance, data)
+    }));
+
+    match result {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => {
+            eprintln!("WARNING ERROR IN EVENT CALLBACK (type {type_:#x}): {e:?}");
+            libfmod::ffi::FMOD_OK
+        }
+        Err(_) => {
+            eprintln!("WARNING PANIC IN EVENT CALLBACK (type {type_:#x})");
+            libfmod::ffi::FMOD_OK
+        }
+    }
+}
+
+unsafe fn dispatch_event_callback(
+    type_: u32,
+    instance: *mut libfmod::ffi::FMOD_STUDIO_EVENTINSTANCE,
+    data: *mut std::ffi::c_void,
+) -> Result<i32, libfmod::Error> {
     use crate::wrap::WrapFMOD;
     let instance = libfmod::EventInstance::from(instance);
+    let instance_data = instance.get_user_data()? as *mut EventUserData;
 
     // Event instances do not (as far as I am aware) share user data with their parents.
     // This means in the callback if an event instance does not have user data set it will try and grab it from its description.
-    // If the description does not have user data, something has gone wrong and we just panic
-    let user_data = (instance.get_user_data().unwrap() as *mut EventUserData)
-        .as_mut()
-        .unwrap_or_else(|| {
-            (instance.get_description().unwrap().get_user_data().unwrap() as *mut EventUserData)
-                .as_mut()
-                .expect("Both event instance and description do not have user data (no callback?)")
-        });
+    // If the description does not have user data either, its bank is being unloaded (see EventDescription#detach_user_data),
+    // and this is an instance being destroyed along with it.
+    let user_data = match instance_data.as_mut() {
+        Some(user_data) => user_data,
+        None => match (instance.get_description()?.get_user_data()? as *mut EventUserData).as_mut()
+        {
+            Some(user_data) => user_data,
+            None => return Ok(libfmod::ffi::FMOD_OK),
+        },
+    };
 
     use libfmod::ffi::*;
+    // Every callback type in fmod_studio_common.h.
+    // Types we don't know about (from a newer FMOD) are still passed along, just with a nil parameter.
     let parameter = match type_ {
         FMOD_STUDIO_EVENT_CALLBACK_CREATED
         | FMOD_STUDIO_EVENT_CALLBACK_STARTING
@@ -85,19 +244,30 @@ unsafe extern "C" fn event_callback(
         | FMOD_STUDIO_EVENT_CALLBACK_STOPPED
         | FMOD_STUDIO_EVENT_CALLBACK_START_FAILED
         | FMOD_STUDIO_EVENT_CALLBACK_REAL_TO_VIRTUAL
-        | FMOD_STUDIO_EVENT_CALLBACK_VIRTUAL_TO_REAL => EventCallbackParameterType::None,
-        FMOD_STUDIO_EVENT_CALLBACK_TIMELINE_MARKER => EventCallbackParameterType::TimelineMarker(
-            libfmod::TimelineMarkerProperties::try_from(
+        | FMOD_STUDIO_EVENT_CALLBACK_VIRTUAL_TO_REAL
+        | FMOD_STUDIO_EVENT_CALLBACK_DESTROYED => EventCallbackParameterType::None,
+        FMOD_STUDIO_EVENT_CALLBACK_TIMELINE_MARKER => {
+            EventCallbackParameterType::TimelineMarker(libfmod::TimelineMarkerProperties::try_from(
                 *(data as *mut FMOD_STUDIO_TIMELINE_MARKER_PROPERTIES),
-            )
-            .unwrap(),
-        ),
-        FMOD_STUDIO_EVENT_CALLBACK_TIMELINE_BEAT => EventCallbackParameterType::TimelineBeat(
-            libfmod::TimelineBeatProperties::try_from(
+            )?)
+        }
+        FMOD_STUDIO_EVENT_CALLBACK_TIMELINE_BEAT => {
+            EventCallbackParameterType::TimelineBeat(libfmod::TimelineBeatProperties::try_from(
                 *(data as *mut FMOD_STUDIO_TIMELINE_BEAT_PROPERTIES),
+            )?)
+        }
+        FMOD_STUDIO_EVENT_CALLBACK_CREATE_PROGRAMMER_SOUND
+        | FMOD_STUDIO_EVENT_CALLBACK_DESTROY_PROGRAMMER_SOUND => {
+            EventCallbackParameterType::ProgrammerSound(data as *mut _)
+        }
+        FMOD_STUDIO_EVENT_CALLBACK_PLUGIN_CREATED | FMOD_STUDIO_EVENT_CALLBACK_PLUGIN_DESTROYED => {
+            EventCallbackParameterType::PluginProperties(
+                *(data as *mut FMOD_STUDIO_PLUGIN_INSTANCE_PROPERTIES),
             )
-            .unwrap(),
-        ),
+        }
+        FMOD_STUDIO_EVENT_CALLBACK_SOUND_PLAYED | FMOD_STUDIO_EVENT_CALLBACK_SOUND_STOPPED => {
+            EventCallbackParameterType::Sound(libfmod::Sound::from(data as *mut _))
+        }
         FMOD_STUDIO_EVENT_CALLBACK_START_EVENT_COMMAND => {
             EventCallbackParameterType::EventInstance(libfmod::EventInstance::from(data as *mut _))
         }
@@ -105,19 +275,48 @@ unsafe extern "C" fn event_callback(
             EventCallbackParameterType::TimelineNested(
                 libfmod::TimelineNestedBeatProperties::try_from(
                     *(data as *mut FMOD_STUDIO_TIMELINE_NESTED_BEAT_PROPERTIES),
-                )
-                .unwrap(),
+                )?,
             )
         }
-        _ => todo!(),
+        _ => EventCallbackParameterType::None,
+    };
+
+    let result = if user_data.mask & type_ == 0 || user_data.callback.is_none() {
+        FMOD_OK
+    } else if user_data.deferred && parameter.can_defer() {
+        EventCallback::create_deferred(instance.wrap_fmod(), type_, parameter, user_data);
+
+        FMOD_OK
+    } else {
+        let reciever = EventCallback::create(instance.wrap_fmod(), type_, parameter, user_data);
+
+        reciever.recv().unwrap_or_else(|e| {
+            eprintln!("Warning callback recv error: {e}");
+            0
+        })
     };
 
-    let reciever = EventCallback::create(instance.wrap_fmod(), type_, parameter, user_data);
+    // The instance is going away, and so is its user data.
+    // Any blocking callback using it has finished by now as we wait on them, deferred ones free it when they're done.
+    if type_ == FMOD_STUDIO_EVENT_CALLBACK_DESTROYED && !instance_data.is_null() {
+        EventUserData::release(instance_data);
+    }
 
-    reciever.recv().unwrap_or_else(|e| {
-        println!("Warning callback recv error: {e}");
-        0
-    })
+    Ok(result)
+}
+
+// set_callback(callback, mask, async: false)
+fn parse_callback_args(
+    args: &[magnus::Value],
+) -> Result<(magnus::Value, std::ffi::c_uint, bool), magnus::Error> {
+    use magnus::scan_args::{get_kwargs, scan_args};
+
+    let args = scan_args::<_, (), (), (), _, ()>(args)?;
+    let (callback, mask) = args.required;
+    let kwargs = get_kwargs::<_, (), (Option<bool>,), ()>(args.keywords, &[], &["async"])?;
+    let (deferred,) = kwargs.optional;
+
+    Ok((callback, mask, deferred.unwrap_or(false)))
 }
 
 opaque_struct!(EventDescription, "Studio", "EventDescription");
@@ -262,10 +461,10 @@ impl EventDescription {
         labelindex: i32,
     ) -> Result<String, magnus::Error> {
         unsafe {
-            use crate::wrap::UnwrapFMOD;
+            use crate::wrap::TryUnwrapFMOD;
 
             let mut retrieved = 0;
-            let id: libfmod::ParameterId = id.unwrap_fmod();
+            let id: libfmod::ParameterId = id.try_unwrap_fmod()?;
             let id = id.into();
 
             let result = libfmod::ffi::FMOD_Studio_EventDescription_GetParameterLabelByID(
@@ -318,52 +517,41 @@ impl EventDescription {
     opaque_struct_method!(has_sustain_point, bool;);
     opaque_struct_method!(create_instance, EventInstance;);
     opaque_struct_method!(get_instance_count, i32;);
+    opaque_struct_list!(
+        get_instance_list, EventInstance;
+        get_instance_count, FMOD_Studio_EventDescription_GetInstanceList, libfmod::EventInstance::from
+    );
 
-    fn get_instance_list(&self) -> Result<Vec<EventInstance>, magnus::Error> {
-        unsafe {
-            use crate::wrap::WrapFMOD;
-
-            let mut array = Vec::with_capacity(1.max(self.get_instance_count()? as usize));
-            let mut count = 0;
+    opaque_struct_method!(load_sample_data, (););
 
-            let result = libfmod::ffi::FMOD_Studio_EventDescription_GetInstanceList(
-                self.0.as_mut_ptr(),
-                array.as_mut_ptr(),
-                array.capacity() as i32,
-                &mut count as *mut _,
-            );
-            //? SAFETY:
-            //? FMOD ensures that count <= capacity.
-            array.set_len(count as _);
+    // Unloading waits for sample data that is still loading, which may need Ruby to read it (see file.rs).
+    fn unload_sample_data(&self) -> Result<(), magnus::Error> {
+        use crate::wrap::WrapFMOD;
 
-            match result {
-                libfmod::ffi::FMOD_OK => Ok(array
-                    .into_iter()
-                    .map(|e| libfmod::EventInstance::from(e).wrap_fmod())
-                    .collect()),
-                error => Err(err_fmod!(
-                    "FMOD_Studio_EventDescription_GetInstanceList",
-                    error
-                )),
-            }
-        }
+        let description = self.0;
+        unsafe { without_gvl_no_ubf(|| description.unload_sample_data()) }
+            .map_err(|e| e.wrap_fmod())
     }
 
-    opaque_struct_method!(load_sample_data, (););
-    opaque_struct_method!(unload_sample_data, (););
     opaque_struct_method!(get_sample_loading_state, LoadingState;);
     opaque_struct_method!(release_all_instances, (););
 
-    fn set_callback(
-        &self,
-        callback: magnus::Value,
-        mask: std::ffi::c_uint,
-    ) -> Result<(), magnus::Error> {
+    fn set_callback(&self, args: &[magnus::Value]) -> Result<(), magnus::Error> {
         use crate::wrap::WrapFMOD;
 
-        self.get_or_create_user_data()?.callback = Some(BoxValue::new(callback));
+        let (callback, mask, deferred) = parse_callback_args(args)?;
+
+        let user_data = self.get_or_create_user_data()?;
+        user_data.callback = Some(BoxValue::new(callback));
+        user_data.mask = mask;
+        user_data.deferred = deferred;
 
-        self.0.set_callback(Some(event_callback), mask).wrap_fmod()
+        self.0
+            .set_callback(
+                Some(event_callback),
+                mask | libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_DESTROYED,
+            )
+            .wrap_fmod()
     }
 
     fn get_user_data(&self) -> Result<Option<magnus::Value>, magnus::Error> {
@@ -377,6 +565,31 @@ impl EventDescription {
         })
     }
 
+    // Event descriptions are only destroyed when their bank is unloaded, so this is called right before that happens.
+    // Nothing can reach the user data afterwards, and it's up to the caller to release it (or reattach it).
+    pub(crate) unsafe fn detach_user_data(&self) -> *mut EventUserData {
+        let ptr = self.0.get_user_data().unwrap_or(std::ptr::null_mut()) as *mut EventUserData;
+
+        if !ptr.is_null() {
+            let _ = self.0.set_callback(None, 0);
+            let _ = self.0.set_user_data(std::ptr::null_mut());
+        }
+
+        ptr
+    }
+
+    // Undoes detach_user_data, for when the bank did not unload after all.
+    pub(crate) unsafe fn reattach_user_data(&self, ptr: *mut EventUserData) {
+        let _ = self.0.set_user_data(ptr as *mut _);
+
+        if let Some(user_data) = ptr.as_ref().filter(|u| u.callback.is_some()) {
+            let _ = self.0.set_callback(
+                Some(event_callback),
+                user_data.mask | libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_DESTROYED,
+            );
+        }
+    }
+
     // This function works just fine here but it.. It'll get interesting for event instances.
     fn get_or_create_user_data(&self) -> Result<&mut EventUserData, magnus::Error> {
         use crate::wrap::WrapFMOD;
@@ -426,7 +639,7 @@ impl EventDescription {
         (release_all_instances, method, 0),
         (get_user_data, method, 0),
         (set_user_data, method, 1),
-        (set_callback, method, 2)
+        (set_callback, method, -1)
     }
 }
 
@@ -460,6 +673,7 @@ impl EventInstance {
     opaque_struct_method!(get_min_max_distance, (f32, f32););
     opaque_struct_method!(release, (););
     opaque_struct_method!(is_virtual, bool;);
+    opaque_struct_method!(get_channel_group, ChannelGroup;);
     opaque_struct_method!(get_parameter_by_name, (f32, f32); (String: ref));
     opaque_struct_method!(set_parameter_by_name, (); (String: ref), (f32), (bool));
     opaque_struct_method!(set_parameter_by_name_with_label, (); (String: ref), (String: ref), (bool));
@@ -474,23 +688,33 @@ impl EventInstance {
         ignoreseekspeed: bool,
     ) -> Result<(), magnus::Error> {
         unsafe {
-            use crate::wrap::UnwrapFMOD;
+            use crate::wrap::TryUnwrapFMOD;
 
-            let ids: Vec<_> = ids
+            let ids = ids
                 .as_slice()
                 .iter()
                 .map(|id| {
-                    let struct_ = RStruct::from_value(*id).unwrap();
-                    let id: libfmod::ParameterId = struct_.unwrap_fmod();
-                    id.into()
+                    let struct_ = RStruct::from_value(*id).ok_or_else(|| {
+                        magnus::Error::new(
+                            magnus::exception::type_error(),
+                            "expected an Array of FMOD::Struct::ParameterId",
+                        )
+                    })?;
+                    let id: libfmod::ParameterId = struct_.try_unwrap_fmod()?;
+                    Ok(id.into())
                 })
-                .collect();
-
-            assert_eq!(
-                ids.len(),
-                values.len(),
-                "The two arrays should be the same length"
-            );
+                .collect::<Result<Vec<_>, magnus::Error>>()?;
+
+            if ids.len() != values.len() {
+                return Err(magnus::Error::new(
+                    magnus::exception::arg_error(),
+                    format!(
+                        "expected as many values as ids, got {} ids and {} values",
+                        ids.len(),
+                        values.len()
+                    ),
+                ));
+            }
 
             let result = libfmod::ffi::FMOD_Studio_EventInstance_SetParametersByIDs(
                 self.0.as_mut_ptr(),
@@ -548,27 +772,44 @@ impl EventInstance {
                 // I hope this makes sense and my logic is sound?
                 if let Some(parent_data) = parent_data {
                     user_data.callback = parent_data.callback.as_mut().map(|c| BoxValue::new(**c));
+                    user_data.mask = parent_data.mask;
+                    user_data.deferred = parent_data.deferred;
                 }
 
                 // And then this function returns to the usual.
                 let user_data = Box::into_raw(Box::new(user_data));
                 self.0.set_user_data(user_data as _).unwrap();
 
+                // We need to know when this instance is destroyed to free its user data.
+                // This keeps whatever callback the instance inherited from its description.
+                self.0
+                    .set_callback(
+                        Some(event_callback),
+                        (*user_data).mask | libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_DESTROYED,
+                    )
+                    .unwrap();
+
                 &mut *user_data
             }))
         }
     }
 
-    fn set_callback(
-        &self,
-        callback: magnus::Value,
-        mask: std::ffi::c_uint,
-    ) -> Result<(), magnus::Error> {
+    fn set_callback(&self, args: &[magnus::Value]) -> Result<(), magnus::Error> {
         use crate::wrap::WrapFMOD;
 
-        self.get_or_create_user_data()?.callback = Some(BoxValue::new(callback));
+        let (callback, mask, deferred) = parse_callback_args(args)?;
+
+        let user_data = self.get_or_create_user_data()?;
+        user_data.callback = Some(BoxValue::new(callback));
+        user_data.mask = mask;
+        user_data.deferred = deferred;
 
-        self.0.set_callback(Some(event_callback), mask).wrap_fmod()
+        self.0
+            .set_callback(
+                Some(event_callback),
+                mask | libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_DESTROYED,
+            )
+            .wrap_fmod()
     }
 
     bind_fn! {
@@ -597,6 +838,7 @@ impl EventInstance {
         (get_min_max_distance, method, 0),
         (release, method, 0),
         (is_virtual, method, 0),
+        (get_channel_group, method, 0),
         (get_parameter_by_name, method, 1),
         (set_parameter_by_name, method, 3),
         (set_parameter_by_name_with_label, method, 3),
@@ -609,11 +851,89 @@ impl EventInstance {
         (get_memory_usage, method, 0),
         (get_user_data, method, 0),
         (set_user_data, method, 1),
-        (set_callback, method, 2)
+        (set_callback, method, -1)
     }
 }
 
+// Event callback types, for callback masks and for comparing against the type passed to a callback.
+fn bind_event_callback_type(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    let callback_type = module.define_module("EventCallbackType")?;
+
+    callback_type.const_set("CREATED", libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_CREATED)?;
+    callback_type.const_set(
+        "DESTROYED",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_DESTROYED,
+    )?;
+    callback_type.const_set(
+        "STARTING",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_STARTING,
+    )?;
+    callback_type.const_set("STARTED", libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_STARTED)?;
+    callback_type.const_set(
+        "RESTARTED",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_RESTARTED,
+    )?;
+    callback_type.const_set("STOPPED", libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_STOPPED)?;
+    callback_type.const_set(
+        "START_FAILED",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_START_FAILED,
+    )?;
+    callback_type.const_set(
+        "CREATE_PROGRAMMER_SOUND",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_CREATE_PROGRAMMER_SOUND,
+    )?;
+    callback_type.const_set(
+        "DESTROY_PROGRAMMER_SOUND",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_DESTROY_PROGRAMMER_SOUND,
+    )?;
+    callback_type.const_set(
+        "PLUGIN_CREATED",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_PLUGIN_CREATED,
+    )?;
+    callback_type.const_set(
+        "PLUGIN_DESTROYED",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_PLUGIN_DESTROYED,
+    )?;
+    callback_type.const_set(
+        "TIMELINE_MARKER",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_TIMELINE_MARKER,
+    )?;
+    callback_type.const_set(
+        "TIMELINE_BEAT",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_TIMELINE_BEAT,
+    )?;
+    callback_type.const_set(
+        "SOUND_PLAYED",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_SOUND_PLAYED,
+    )?;
+    callback_type.const_set(
+        "SOUND_STOPPED",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_SOUND_STOPPED,
+    )?;
+    callback_type.const_set(
+        "REAL_TO_VIRTUAL",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_REAL_TO_VIRTUAL,
+    )?;
+    callback_type.const_set(
+        "VIRTUAL_TO_REAL",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_VIRTUAL_TO_REAL,
+    )?;
+    callback_type.const_set(
+        "START_EVENT_COMMAND",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_START_EVENT_COMMAND,
+    )?;
+    callback_type.const_set(
+        "NESTED_TIMELINE_BEAT",
+        libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_NESTED_TIMELINE_BEAT,
+    )?;
+    callback_type.const_set("ALL", libfmod::ffi::FMOD_STUDIO_EVENT_CALLBACK_ALL)?;
+
+    Ok(())
+}
+
 pub fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
     EventDescription::bind(module)?;
-    EventInstance::bind(module)
+    EventInstance::bind(module)?;
+    ProgrammerSoundProperties::bind(module)?;
+    bind_event_callback_type(module)
 }
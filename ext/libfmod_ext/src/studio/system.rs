@@ -18,6 +18,7 @@
 use magnus::value::BoxValue;
 use magnus::RStruct;
 
+use crate::core::sound::Sound;
 use crate::enums::LoadMemoryMode;
 use crate::err_fmod;
 use crate::studio::command_replay::CommandReplay;
@@ -25,13 +26,20 @@ use crate::studio::event::EventDescription;
 use crate::studio::vca::Vca;
 use crate::thread::without_gvl_no_ubf;
 #[allow(unused_imports)]
-use crate::{bind_fn, opaque_struct, opaque_struct_function, opaque_struct_method};
-use crate::{callback::StudioSystemCallback, studio::bank::Bank};
+use crate::{
+    bind_fn, opaque_struct, opaque_struct_function, opaque_struct_list, opaque_struct_method,
+};
+use crate::{
+    callback::{without_gvl_dispatching, StudioSystemCallback},
+    studio::bank::{Bank, DetachedUserData},
+};
 
 #[derive(Default)]
 pub struct StudioUserData {
     pub callback: Option<BoxValue<magnus::Value>>,
     pub userdata: Option<BoxValue<magnus::Value>>,
+    // Non-blocking bank loads (FMOD::Studio::BankLoad) that update checks on until they finish.
+    pub bank_loads: Vec<BoxValue<magnus::Value>>,
 }
 
 opaque_struct!(Studio, "Studio", "System");
@@ -63,16 +71,203 @@ impl Studio {
     // We update the system without the GVL so synchronous updates work.
     // If we did not, this would block all threads (remember ruby doesn't run threads in parallel) including the one responsible for running callbacks.
     fn update(&self) -> Result<(), magnus::Error> {
+        crate::callback::dispatch_queued();
+
         unsafe {
             use crate::wrap::WrapFMOD;
 
-            let result = without_gvl_no_ubf(|system| system.update(), self.0);
+            let system = self.0;
+            without_gvl_no_ubf(|| system.update()).map_err(|e| e.wrap_fmod())?;
+        }
+
+        crate::callback::dispatch_queued();
+        self.poll_bank_loads()?;
+        crate::file::release_unloaded_banks()?;
+        crate::callback::raise_pending_error()
+    }
+
+    // Polls every pending BankLoad, which runs its block once the bank has finished loading.
+    fn poll_bank_loads(&self) -> Result<(), magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let ptr = self.0.get_user_data().map_err(|e| e.wrap_fmod())? as *mut StudioUserData;
+        let user_data = match unsafe { ptr.as_mut() } {
+            Some(user_data) => user_data,
+            None => return Ok(()),
+        };
+
+        // Blocks can start more loads (or release the system), so we don't hold on to the list while calling them.
+        let loads = std::mem::take(&mut user_data.bank_loads);
+        let mut pending = Vec::with_capacity(loads.len());
+        let mut error = None;
+
+        for load in loads {
+            match load.funcall::<_, _, bool>("poll", ()) {
+                Ok(true) => {}
+                Ok(false) => pending.push(load),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
+
+        if let Some(user_data) = unsafe {
+            (self.0.get_user_data().map_err(|e| e.wrap_fmod())? as *mut StudioUserData).as_mut()
+        } {
+            user_data.bank_loads.extend(pending);
+        }
+
+        error.map_or(Ok(()), Err)
+    }
+
+    // Releasing the system unloads every bank and stops any callbacks from being called, so we can free all of our user data.
+    fn release(&self) -> Result<(), magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let ptr = self.0.get_user_data().map_err(|e| e.wrap_fmod())? as *mut StudioUserData;
+        let core = self.0.get_core_system().map_err(|e| e.wrap_fmod())?;
+
+        // A system that was never initialized has no banks, and FMOD errors instead of listing none.
+        // Whatever goes wrong here, the system itself still has to be released.
+        let mut error = None;
+        let mut detached = Vec::new();
+        for bank in self.get_bank_list().unwrap_or_default() {
+            match bank.detach_user_data() {
+                Ok(user_data) => detached.push(user_data),
+                Err(e) => {
+                    error.get_or_insert(e);
+                }
+            }
+        }
 
-            result.map_err(|e| e.wrap_fmod())
+        if let Err(e) = self.0.release() {
+            detached.into_iter().for_each(DetachedUserData::reattach);
+            return Err(e.wrap_fmod());
         }
+        detached.into_iter().for_each(DetachedUserData::release);
+        // Every bank is gone now, so nothing is reading from their sources anymore.
+        if let Err(e) = crate::file::release_unloaded_banks() {
+            error.get_or_insert(e);
+        }
+        // This releases the core system too, which may have had the file system handler.
+        crate::file::set_file_system(core.as_mut_ptr(), None);
+
+        if !ptr.is_null() {
+            unsafe { drop(Box::from_raw(ptr)) }
+        }
+
+        error.map_or(Ok(()), Err)
     }
 
-    opaque_struct_method!(release, (););
+    opaque_struct_method!(get_core_system, crate::core::system::System;);
+
+    fn get_sound_info(&self, key: String) -> Result<RStruct, magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let key =
+            std::ffi::CString::new(key).map_err(|e| libfmod::Error::StringNul(e).wrap_fmod())?;
+
+        unsafe {
+            let mut info = std::mem::zeroed::<libfmod::ffi::FMOD_STUDIO_SOUND_INFO>();
+
+            match libfmod::ffi::FMOD_Studio_System_GetSoundInfo(
+                self.0.as_mut_ptr(),
+                key.as_ptr(),
+                &mut info,
+            ) {
+                libfmod::ffi::FMOD_OK => {}
+                error => return Err(err_fmod!("FMOD_Studio_System_GetSoundInfo", error)),
+            }
+
+            // FMOD points at the start of the bank, and the sound is fileoffset bytes into it.
+            let sound_info: RStruct = info.wrap_fmod();
+            if info.mode & libfmod::ffi::FMOD_OPENMEMORY_POINT != 0 {
+                if let Some((bank, offset)) = self.find_pinned_bank(info.name_or_data as _)? {
+                    sound_info.aset("name_or_data", bank)?;
+                    let exinfo: RStruct = sound_info.aref("exinfo")?;
+                    exinfo.aset("file_offset", offset + info.exinfo.fileoffset as usize)?;
+                }
+            }
+
+            Ok(sound_info)
+        }
+    }
+
+    // Finds the String a bank loaded with LoadMemoryMode::MemoryPoint was pinned from, if ptr points into it,
+    // along with how far into it ptr is. Banks FMOD copied are in FMOD's own memory, and aren't found.
+    unsafe fn find_pinned_bank(
+        &self,
+        ptr: *const u8,
+    ) -> Result<Option<(magnus::RString, usize)>, magnus::Error> {
+        use crate::file::BankSource;
+        use crate::wrap::{UnwrapFMOD, WrapFMOD};
+
+        for bank in self.get_bank_list()? {
+            let bank: libfmod::Bank = bank.unwrap_fmod();
+            let source = bank.get_user_data().map_err(|e| e.wrap_fmod())? as *const BankSource;
+
+            if let Some(BankSource::Pinned(string)) = source.as_ref() {
+                let data = string.as_slice();
+                let start = data.as_ptr() as usize;
+
+                if (start..start + data.len()).contains(&(ptr as usize)) {
+                    return Ok(Some((**string, ptr as usize - start)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    // Opens the sound behind an audio table key, returning it and the subsound to play.
+    // This skips the round trip through Ruby that get_sound_info + Core::System#create_sound would take.
+    fn create_sound_by_key(
+        &self,
+        key: String,
+        mode: std::ffi::c_uint,
+    ) -> Result<(Sound, i32), magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let key =
+            std::ffi::CString::new(key).map_err(|e| libfmod::Error::StringNul(e).wrap_fmod())?;
+
+        unsafe {
+            let mut info = std::mem::zeroed::<libfmod::ffi::FMOD_STUDIO_SOUND_INFO>();
+
+            match libfmod::ffi::FMOD_Studio_System_GetSoundInfo(
+                self.0.as_mut_ptr(),
+                key.as_ptr(),
+                &mut info,
+            ) {
+                libfmod::ffi::FMOD_OK => {}
+                error => return Err(err_fmod!("FMOD_Studio_System_GetSoundInfo", error)),
+            }
+
+            let core = self
+                .0
+                .get_core_system()
+                .map_err(|e| e.wrap_fmod())?
+                .as_mut_ptr();
+
+            // info points into the bank, which stays loaded while we're creating the sound.
+            without_gvl_no_ubf(|| {
+                let mut sound = std::ptr::null_mut();
+
+                match libfmod::ffi::FMOD_System_CreateSound(
+                    core,
+                    info.name_or_data,
+                    info.mode | mode,
+                    &mut info.exinfo,
+                    &mut sound,
+                ) {
+                    libfmod::ffi::FMOD_OK => {
+                        Ok((libfmod::Sound::from(sound).wrap_fmod(), info.subsoundindex))
+                    }
+                    error => Err(err_fmod!("FMOD_System_CreateSound", error)),
+                }
+            })
+        }
+    }
     opaque_struct_method!(get_event, EventDescription; (String: ref));
     opaque_struct_method!(get_vca, Vca; (String: ref));
     opaque_struct_method!(get_bank, Bank; (String: ref));
@@ -130,10 +325,10 @@ impl Studio {
         labelindex: i32,
     ) -> Result<String, magnus::Error> {
         unsafe {
-            use crate::wrap::UnwrapFMOD;
+            use crate::wrap::TryUnwrapFMOD;
 
             let mut retrieved = 0;
-            let id: libfmod::ParameterId = id.unwrap_fmod();
+            let id: libfmod::ParameterId = id.try_unwrap_fmod()?;
             let id = id.into();
 
             let result = libfmod::ffi::FMOD_Studio_System_GetParameterLabelByID(
@@ -177,23 +372,33 @@ impl Studio {
         ignoreseekspeed: bool,
     ) -> Result<(), magnus::Error> {
         unsafe {
-            use crate::wrap::UnwrapFMOD;
+            use crate::wrap::TryUnwrapFMOD;
 
-            let ids: Vec<_> = ids
+            let ids = ids
                 .as_slice()
                 .iter()
                 .map(|id| {
-                    let struct_ = RStruct::from_value(*id).unwrap();
-                    let id: libfmod::ParameterId = struct_.unwrap_fmod();
-                    id.into()
+                    let struct_ = RStruct::from_value(*id).ok_or_else(|| {
+                        magnus::Error::new(
+                            magnus::exception::type_error(),
+                            "expected an Array of FMOD::Struct::ParameterId",
+                        )
+                    })?;
+                    let id: libfmod::ParameterId = struct_.try_unwrap_fmod()?;
+                    Ok(id.into())
                 })
-                .collect();
-
-            assert_eq!(
-                ids.len(),
-                values.len(),
-                "The two arrays should be the same length"
-            );
+                .collect::<Result<Vec<_>, magnus::Error>>()?;
+
+            if ids.len() != values.len() {
+                return Err(magnus::Error::new(
+                    magnus::exception::arg_error(),
+                    format!(
+                        "expected as many values as ids, got {} ids and {} values",
+                        ids.len(),
+                        values.len()
+                    ),
+                ));
+            }
 
             let result = libfmod::ffi::FMOD_Studio_System_SetParametersByIDs(
                 self.0.as_mut_ptr(),
@@ -218,10 +423,10 @@ impl Studio {
 
     fn lookup_path(&self, id: RStruct) -> Result<String, magnus::Error> {
         unsafe {
-            use crate::wrap::UnwrapFMOD;
+            use crate::wrap::TryUnwrapFMOD;
 
             let mut retrieved = 0;
-            let id: libfmod::Guid = id.unwrap_fmod();
+            let id: libfmod::Guid = id.try_unwrap_fmod()?;
             let id = id.into();
 
             let result = libfmod::ffi::FMOD_Studio_System_LookupPath(
@@ -252,9 +457,45 @@ impl Studio {
         }
     }
 
-    opaque_struct_method!(unload_all, (););
-    opaque_struct_method!(flush_commands, (););
-    opaque_struct_method!(flush_sample_loading, (););
+    fn unload_all(&self) -> Result<(), magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let detached = self
+            .get_bank_list()?
+            .into_iter()
+            .map(|bank| bank.detach_user_data())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match self.0.unload_all() {
+            Ok(()) => {
+                detached.into_iter().for_each(DetachedUserData::release);
+                Ok(())
+            }
+            Err(e) => {
+                detached.into_iter().for_each(DetachedUserData::reattach);
+                Err(e.wrap_fmod())
+            }
+        }
+    }
+
+    // These wait on FMOD's loading thread, which may need Ruby to read files (see file.rs).
+    // Holding the GVL while waiting would deadlock, so we release it like update does.
+    // When dispatching on update, FMOD may also be waiting on callbacks queued for update, which we run meanwhile.
+    fn flush_commands(&self) -> Result<(), magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let system = self.0;
+        unsafe { without_gvl_dispatching(|| system.flush_commands()) }.map_err(|e| e.wrap_fmod())
+    }
+
+    fn flush_sample_loading(&self) -> Result<(), magnus::Error> {
+        use crate::wrap::WrapFMOD;
+
+        let system = self.0;
+        unsafe { without_gvl_dispatching(|| system.flush_sample_loading()) }
+            .map_err(|e| e.wrap_fmod())
+    }
+
     opaque_struct_method!(start_command_capture, (); (String: ref), (std::ffi::c_uint));
     opaque_struct_method!(stop_command_capture, (););
     opaque_struct_method!(load_command_replay, CommandReplay; (String: ref), (std::ffi::c_uint));
@@ -267,122 +508,253 @@ impl Studio {
     opaque_struct_method!(set_listener_weight, (); (i32), (f32));
 
     // Because this function *can* be blocking we HAVE to do this to avoid it deadlocking on callbacks.
+    // With LoadBankFlags::NONBLOCKING we return a BankLoad instead, which update polls until the bank has loaded.
     fn load_bank_file(
         &self,
         filename: String,
         flags: std::ffi::c_uint,
-    ) -> Result<Bank, magnus::Error> {
-        unsafe {
-            use crate::wrap::WrapFMOD;
+    ) -> Result<magnus::Value, magnus::Error> {
+        use crate::wrap::WrapFMOD;
+        use magnus::{Class, IntoValue};
 
-            without_gvl_no_ubf(
-                |(system, filename, flags)| system.load_bank_file(&filename, flags).wrap_fmod(),
-                (self.0, filename, flags),
-            )
+        let system = self.0;
+        let bank: Bank =
+            unsafe { without_gvl_dispatching(|| system.load_bank_file(&filename, flags)) }
+                .map_err(|e| e.wrap_fmod())?
+                .wrap_fmod();
+
+        if flags & libfmod::ffi::FMOD_STUDIO_LOAD_BANK_NONBLOCKING == 0 {
+            return Ok(bank.into_value());
         }
+
+        let on_complete = magnus::block::block_given()
+            .then(magnus::block::block_proc)
+            .transpose()?;
+        let load: magnus::Value = magnus::class::object()
+            .const_get::<_, magnus::RModule>("FMOD")?
+            .const_get::<_, magnus::RModule>("Studio")?
+            .const_get::<_, magnus::RClass>("BankLoad")?
+            .new_instance((*self, bank, on_complete))?;
+
+        self.get_or_create_user_data()?
+            .bank_loads
+            .push(BoxValue::new(load));
+
+        Ok(load)
     }
 
-    // libfmod does NOT define this function correctly.
-    // Because of this we have to write it ourselves-
-    fn load_bank_memory(
+    // Loads a bank from `source`, which FMOD reads from through file callbacks instead of a path.
+    // A String is read from directly, without copying it or calling into Ruby. It is frozen until the bank is unloaded.
+    // Anything else is treated like an IO, it needs to respond to read, seek and close (see file.rs).
+    // FMOD may keep reading to stream sample data, so the source is only let go of (and closed) once the bank is unloaded.
+    fn load_bank_custom(
         &self,
-        data: Vec<u8>,
-        mode: &LoadMemoryMode,
+        source: magnus::Value,
         flags: std::ffi::c_uint,
     ) -> Result<Bank, magnus::Error> {
-        use crate::wrap::UnwrapFMOD;
-        use crate::wrap::WrapFMOD;
+        use crate::file::*;
+
+        let (source, info) = match magnus::RString::from_value(source) {
+            Some(data) => {
+                let file = MemoryFile::new(data);
+                let info = libfmod::ffi::FMOD_STUDIO_BANK_INFO {
+                    size: std::mem::size_of::<libfmod::ffi::FMOD_STUDIO_BANK_INFO>() as _,
+                    userdata: file as _,
+                    userdatalength: 0,
+                    opencallback: Some(memory_open),
+                    closecallback: Some(memory_close),
+                    readcallback: Some(memory_read),
+                    seekcallback: Some(memory_seek),
+                };
+
+                (BankSource::Memory(file), info)
+            }
+            None => {
+                let file = RubyFile::new(source);
+                let info = libfmod::ffi::FMOD_STUDIO_BANK_INFO {
+                    size: std::mem::size_of::<libfmod::ffi::FMOD_STUDIO_BANK_INFO>() as _,
+                    userdata: file as _,
+                    userdatalength: 0,
+                    opencallback: Some(bank_open),
+                    closecallback: Some(file_close),
+                    readcallback: Some(file_read),
+                    seekcallback: Some(file_seek),
+                };
+
+                (BankSource::Io(file), info)
+            }
+        };
 
         unsafe {
-            without_gvl_no_ubf(
-                |(system, data, mode, flags)| {
-                    let mut bank = std::ptr::null_mut();
+            let system = self.0;
+            let mut bank = std::ptr::null_mut();
+            // IO callbacks need Ruby, so we have to release the GVL while FMOD loads.
+            let result = without_gvl_dispatching(|| {
+                libfmod::ffi::FMOD_Studio_System_LoadBankCustom(
+                    system.as_mut_ptr(),
+                    &info,
+                    flags,
+                    &mut bank,
+                )
+            });
+
+            Self::finish_bank_load("FMOD_Studio_System_LoadBankCustom", result, bank, source)
+        }
+    }
+
+    // load_bank_memory(data, mode, flags, offset = 0, length = nil)
+    // data is a String or an array of bytes, of which FMOD loads `length` bytes from `offset` (by default all of it).
+    // With LoadMemoryMode::Memory FMOD copies them. With MemoryPoint FMOD uses the String as is, so it is frozen and
+    // kept alive until the bank is unloaded. FMOD can only point at aligned memory, so we raise if data + offset isn't.
+    fn load_bank_memory(&self, args: &[magnus::Value]) -> Result<Bank, magnus::Error> {
+        use crate::file::BankSource;
+        use crate::wrap::{UnwrapFMOD, WrapFMOD};
+
+        let args = magnus::scan_args::scan_args::<
+            (magnus::Value, magnus::Value, std::ffi::c_uint),
+            (Option<usize>, Option<usize>),
+            (),
+            (),
+            (),
+            (),
+        >(args)?;
+        let (data, mode, flags) = args.required;
+        let (offset, length) = args.optional;
+
+        let mode: libfmod::FMOD_STUDIO_LOAD_MEMORY_MODE =
+            mode.try_convert::<&LoadMemoryMode>()?.unwrap_fmod();
+        let string = magnus::RString::from_value(data);
+        let bytes: Vec<u8>;
+        let slice = match string {
+            Some(string) => unsafe { string.as_slice() },
+            None => {
+                bytes = data.try_convert()?;
+                bytes.as_slice()
+            }
+        };
+
+        let offset = offset.unwrap_or(0);
+        let length = length.unwrap_or_else(|| slice.len().saturating_sub(offset));
+        let slice = offset
+            .checked_add(length)
+            .and_then(|end| slice.get(offset..end))
+            .ok_or_else(|| {
+                magnus::Error::new(
+                    magnus::exception::index_error(),
+                    format!(
+                        "{length} bytes at offset {offset} are out of range for {} bytes",
+                        slice.len()
+                    ),
+                )
+            })?;
+
+        if mode == libfmod::FMOD_STUDIO_LOAD_MEMORY_MODE::FMOD_STUDIO_LOAD_MEMORY_POINT {
+            let Some(string) = string else {
+                return Err(magnus::Error::new(
+                    magnus::exception::arg_error(),
+                    "LoadMemoryMode::MemoryPoint needs a String",
+                ));
+            };
+
+            let alignment = libfmod::ffi::FMOD_STUDIO_LOAD_MEMORY_ALIGNMENT as usize;
+            if slice.as_ptr() as usize % alignment != 0 {
+                return Err(magnus::Error::new(
+                    magnus::exception::arg_error(),
+                    format!(
+                        "LoadMemoryMode::MemoryPoint needs the bank aligned to {alignment} bytes, \
+                         use LoadMemoryMode::Memory to copy it instead"
+                    ),
+                ));
+            }
 
-                    let result = libfmod::ffi::FMOD_Studio_System_LoadBankMemory(
+            string.freeze();
+            let (ptr, len) = (slice.as_ptr(), slice.len());
+
+            unsafe {
+                let system = self.0;
+                let mut bank = std::ptr::null_mut();
+                // The string is frozen, so nothing can move or free it while we don't hold the GVL.
+                let result = without_gvl_dispatching(|| {
+                    libfmod::ffi::FMOD_Studio_System_LoadBankMemory(
                         system.as_mut_ptr(),
-                        data.as_ptr() as _,
-                        data.len() as _,
+                        ptr as _,
+                        len as _,
                         mode.into(),
                         flags,
                         &mut bank,
-                    );
-
-                    match result {
-                        libfmod::ffi::FMOD_OK => Ok(libfmod::Bank::from(bank).wrap_fmod()),
-                        error => Err(err_fmod!("FMOD_Studio_System_LoadBankMemory", error)),
-                    }
-                },
-                (self.0, data, mode.unwrap_fmod(), flags),
-            )
+                    )
+                });
+
+                return Self::finish_bank_load(
+                    "FMOD_Studio_System_LoadBankMemory",
+                    result,
+                    bank,
+                    BankSource::Pinned(BoxValue::new(string)),
+                );
+            }
         }
-    }
 
-    opaque_struct_method!(get_bank_count, i32;);
+        // Other threads could change the string while we don't hold the GVL, so FMOD copies our own copy.
+        let data = slice.to_vec();
 
-    fn get_bank_list(&self) -> Result<Vec<Bank>, magnus::Error> {
         unsafe {
-            use crate::wrap::WrapFMOD;
-
-            let mut array = Vec::with_capacity(1.max(self.get_bank_count()? as usize));
-            let mut count = 0;
-
-            let result = libfmod::ffi::FMOD_Studio_System_GetBankList(
-                self.0.as_mut_ptr(),
-                array.as_mut_ptr(),
-                array.capacity() as i32,
-                &mut count as *mut _,
-            );
-            //? SAFETY:
-            //? FMOD ensures that count <= capacity.
-            array.set_len(count as _);
+            let system = self.0;
+            let mut bank = std::ptr::null_mut();
+            let result = without_gvl_dispatching(|| {
+                libfmod::ffi::FMOD_Studio_System_LoadBankMemory(
+                    system.as_mut_ptr(),
+                    data.as_ptr() as _,
+                    data.len() as _,
+                    mode.into(),
+                    flags,
+                    &mut bank,
+                )
+            });
 
             match result {
-                libfmod::ffi::FMOD_OK => Ok(array
-                    .into_iter()
-                    .map(|e| libfmod::Bank::from(e).wrap_fmod())
-                    .collect()),
-                error => Err(err_fmod!("FMOD_Studio_System_GetBankList", error)),
+                libfmod::ffi::FMOD_OK => Ok(libfmod::Bank::from(bank).wrap_fmod()),
+                error => Err(err_fmod!("FMOD_Studio_System_LoadBankMemory", error)),
             }
         }
     }
 
-    opaque_struct_method!(get_parameter_description_count, i32;);
-
-    fn get_parameter_description_list(&self) -> Result<Vec<RStruct>, magnus::Error> {
-        unsafe {
-            use crate::wrap::WrapFMOD;
-
-            let mut array =
-                Vec::with_capacity(1.max(self.get_parameter_description_count()? as usize));
-            let mut count = 0;
-
-            let result = libfmod::ffi::FMOD_Studio_System_GetParameterDescriptionList(
-                self.0.as_mut_ptr(),
-                array.as_mut_ptr(),
-                array.capacity() as i32,
-                &mut count as *mut _,
-            );
-            //? SAFETY:
-            //? FMOD ensures that count <= capacity.
-            array.set_len(count as _);
+    // Hands the source over to the bank if it loaded, or lets go of it if it didn't.
+    unsafe fn finish_bank_load(
+        function: &str,
+        result: libfmod::ffi::FMOD_RESULT,
+        bank: *mut libfmod::ffi::FMOD_STUDIO_BANK,
+        source: crate::file::BankSource,
+    ) -> Result<Bank, magnus::Error> {
+        use crate::wrap::WrapFMOD;
 
-            match result {
-                libfmod::ffi::FMOD_OK => Ok(array
-                    .into_iter()
-                    .map(|e| {
-                        libfmod::ParameterDescription::try_from(e)
-                            .unwrap()
-                            .wrap_fmod()
-                    })
-                    .collect()),
-                error => Err(err_fmod!(
-                    "FMOD_Studio_System_GetParameterDescriptionList",
-                    error
-                )),
+        match result {
+            libfmod::ffi::FMOD_OK => {
+                libfmod::ffi::FMOD_Studio_Bank_SetUserData(
+                    bank,
+                    Box::into_raw(Box::new(source)) as _,
+                );
+                Ok(libfmod::Bank::from(bank).wrap_fmod())
+            }
+            error => {
+                source.release()?;
+                Err(err_fmod!(function, error))
             }
         }
     }
 
+    opaque_struct_method!(get_bank_count, i32;);
+    opaque_struct_list!(
+        get_bank_list, Bank;
+        get_bank_count, FMOD_Studio_System_GetBankList, libfmod::Bank::from
+    );
+
+    opaque_struct_method!(get_parameter_description_count, i32;);
+    opaque_struct_list!(
+        get_parameter_description_list, RStruct;
+        get_parameter_description_count, FMOD_Studio_System_GetParameterDescriptionList,
+        std::convert::identity
+    );
+
     opaque_struct_method!(get_cpu_usage, (RStruct, RStruct););
     opaque_struct_method!(get_buffer_usage, RStruct;);
     opaque_struct_method!(reset_buffer_usage, (););
@@ -415,12 +787,9 @@ impl Studio {
                 &mut *(userdata as *mut _),
             );
 
-            #[cfg(feature = "track-callbacks")]
-            println!("Waiting for callback response");
-
             // Wait for a callback result and finish.
             reciever.recv().unwrap_or_else(|e| {
-                println!("Warning callback recv error: {e}");
+                eprintln!("Warning callback recv error: {e}");
                 0
             })
         }
@@ -467,11 +836,15 @@ impl Studio {
         (init, method, 3),
         (update, method, 0),
         (release, method, 0),
+        (get_core_system, method, 0),
+        (get_sound_info, method, 1),
+        (create_sound_by_key, method, 2),
         (get_event, method, 1),
         (get_vca, method, 1),
         (get_bank, method, 1),
         (load_bank_file, method, 2),
-        (load_bank_memory, method, 3),
+        (load_bank_memory, method, -1),
+        (load_bank_custom, method, 2),
         (get_event_by_id, method, 1),
         (get_vca_by_id, method, 1),
         (get_bank_by_id, method, 1),
@@ -514,8 +887,29 @@ impl Studio {
     }
 }
 
+fn bind_load_bank_flags(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    let flags = module.define_module("LoadBankFlags")?;
+
+    flags.const_set("NORMAL", libfmod::ffi::FMOD_STUDIO_LOAD_BANK_NORMAL)?;
+    flags.const_set(
+        "NONBLOCKING",
+        libfmod::ffi::FMOD_STUDIO_LOAD_BANK_NONBLOCKING,
+    )?;
+    flags.const_set(
+        "DECOMPRESS_SAMPLES",
+        libfmod::ffi::FMOD_STUDIO_LOAD_BANK_DECOMPRESS_SAMPLES,
+    )?;
+    flags.const_set(
+        "UNENCRYPTED",
+        libfmod::ffi::FMOD_STUDIO_LOAD_BANK_UNENCRYPTED,
+    )?;
+
+    Ok(())
+}
+
 pub fn bind_system(studio: impl magnus::Module) -> Result<(), magnus::Error> {
     Studio::bind(studio)?;
+    bind_load_bank_flags(studio)?;
 
     Ok(())
 }
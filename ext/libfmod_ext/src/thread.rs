@@ -15,8 +15,31 @@
 // You should have received a copy of the GNU General Public License
 // along with libfmod.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cell::Cell;
 use std::ffi::c_void;
 
+thread_local! {
+    // Set while this thread is running a closure passed to without_gvl or without_gvl_no_ubf.
+    static GVL_RELEASED: Cell<bool> = Cell::new(false);
+}
+
+// Whether this thread is running a closure passed to without_gvl or without_gvl_no_ubf.
+pub fn gvl_released() -> bool {
+    GVL_RELEASED.with(Cell::get)
+}
+
+// Runs func with the GVL released, marking this thread as not holding it.
+unsafe fn call_released<Func, FuncReturn>(mut func: Func) -> FuncReturn
+where
+    Func: FnMut() -> FuncReturn,
+{
+    let released = GVL_RELEASED.with(|r| r.replace(true));
+    let result = func();
+    GVL_RELEASED.with(|r| r.set(released));
+
+    result
+}
+
 // Type safe wrapper around rb_thread_call_without_gvl.
 // This function is still very unsafe and should be used sparingly.
 pub unsafe fn without_gvl_no_ubf<Func, FuncReturn>(func: Func) -> FuncReturn
@@ -27,9 +50,9 @@ where
     where
         Func: FnMut() -> FuncReturn,
     {
-        let mut func: Func = *Box::from_raw(data as *mut Func);
+        let func: Func = *Box::from_raw(data as *mut Func);
 
-        Box::into_raw(Box::new(func())) as *mut _
+        Box::into_raw(Box::new(call_released(func))) as *mut _
     }
 
     //? SAFETY: We box the function and args to pass them over the FFI boundary.
@@ -56,9 +79,9 @@ where
     where
         Func: FnMut() -> FuncReturn,
     {
-        let mut func: Func = *Box::from_raw(data as _);
+        let func: Func = *Box::from_raw(data as _);
 
-        Box::into_raw(Box::new(func())) as _
+        Box::into_raw(Box::new(call_released(func))) as _
     }
 
     unsafe extern "C" fn anon_unblock<Unblock>(data: *mut c_void)
@@ -84,6 +107,41 @@ where
     *Box::from_raw(result as _)
 }
 
+// Type safe wrapper around rb_thread_call_with_gvl.
+// Reacquires the GVL if this thread released it with one of the functions above, otherwise calls func directly.
+// Must not be called from a thread Ruby does not know about (like FMOD's own threads).
+pub unsafe fn with_gvl<Func, FuncReturn>(mut func: Func) -> FuncReturn
+where
+    Func: FnMut() -> FuncReturn,
+{
+    unsafe extern "C" fn anon_func<Func, FuncReturn>(data: *mut c_void) -> *mut c_void
+    where
+        Func: FnMut() -> FuncReturn,
+    {
+        let mut func: Func = *Box::from_raw(data as _);
+
+        GVL_RELEASED.with(|r| r.set(false));
+        let result = func();
+        GVL_RELEASED.with(|r| r.set(true));
+
+        Box::into_raw(Box::new(result)) as _
+    }
+
+    if !GVL_RELEASED.with(Cell::get) {
+        return func();
+    }
+
+    //? SAFETY: We box the function and args to pass them over the FFI boundary.
+    let boxed_func = Box::new(func);
+
+    let result = rb_sys::rb_thread_call_with_gvl(
+        Some(anon_func::<Func, FuncReturn>),
+        Box::into_raw(boxed_func) as *mut _,
+    );
+
+    *Box::from_raw(result as _)
+}
+
 // Type safe wrapper around rb_thread_create.
 // This function is still very unsafe and should be used sparingly.
 pub unsafe fn spawn_rb_thread<Func>(func: Func) -> u64
@@ -85,36 +85,43 @@ fn bind_userproperty(module: impl magnus::Module) -> Result<(), magnus::Error> {
 }
 
 // FIXME: this will either segfault or leak memory when using string values. THIS IS BAD.
-impl crate::wrap::UnwrapFMOD<libfmod::FMOD_STUDIO_USER_PROPERTY> for RStruct {
-    fn unwrap_fmod(self) -> libfmod::FMOD_STUDIO_USER_PROPERTY {
-        let name: String = self.aref("name").unwrap();
+impl crate::wrap::TryUnwrapFMOD<libfmod::FMOD_STUDIO_USER_PROPERTY> for RStruct {
+    fn try_unwrap_fmod(self) -> Result<libfmod::FMOD_STUDIO_USER_PROPERTY, magnus::Error> {
+        use crate::wrap::{field_type_error, UnwrapFMOD};
+
+        let name: String = self
+            .aref("name")
+            .map_err(field_type_error("UserProperty", "name"))?;
         let name = name.as_ptr() as _;
         let type_ = self
             .aref::<_, &crate::enums::UserPropertyType>("type")
-            .unwrap()
+            .map_err(field_type_error("UserProperty", "type"))?
             .unwrap_fmod();
+        let data_error = field_type_error("UserProperty", "data");
 
         let __bindgen_anon_1 = match type_ {
             libfmod::FMOD_STUDIO_USER_PROPERTY_TYPE::FMOD_STUDIO_USER_PROPERTY_TYPE_INTEGER => {
                 libfmod::FMOD_STUDIO_USER_PROPERTY__bindgen_ty_1 {
-                    intvalue: self.aref("data").unwrap(),
+                    intvalue: self.aref("data").map_err(data_error)?,
                 }
             }
             libfmod::FMOD_STUDIO_USER_PROPERTY_TYPE::FMOD_STUDIO_USER_PROPERTY_TYPE_BOOLEAN => {
                 libfmod::FMOD_STUDIO_USER_PROPERTY__bindgen_ty_1 {
-                    boolvalue: self.aref("data").unwrap(),
+                    boolvalue: self.aref("data").map_err(data_error)?,
                 }
             }
             libfmod::FMOD_STUDIO_USER_PROPERTY_TYPE::FMOD_STUDIO_USER_PROPERTY_TYPE_FLOAT => {
                 libfmod::FMOD_STUDIO_USER_PROPERTY__bindgen_ty_1 {
-                    floatvalue: self.aref("data").unwrap(),
+                    floatvalue: self.aref("data").map_err(data_error)?,
                 }
             }
             libfmod::FMOD_STUDIO_USER_PROPERTY_TYPE::FMOD_STUDIO_USER_PROPERTY_TYPE_STRING => {
                 libfmod::FMOD_STUDIO_USER_PROPERTY__bindgen_ty_1 {
-                    stringvalue: std::ffi::CString::new(self.aref::<_, String>("data").unwrap())
-                        .unwrap()
-                        .into_raw(),
+                    stringvalue: std::ffi::CString::new(
+                        self.aref::<_, String>("data").map_err(data_error)?,
+                    )
+                    .map_err(|e| magnus::Error::new(magnus::exception::arg_error(), e.to_string()))?
+                    .into_raw(),
                 }
             }
             libfmod::FMOD_STUDIO_USER_PROPERTY_TYPE::FMOD_STUDIO_USER_PROPERTY_TYPE_FORCEINT => {
@@ -122,16 +129,17 @@ impl crate::wrap::UnwrapFMOD<libfmod::FMOD_STUDIO_USER_PROPERTY> for RStruct {
             }
         };
 
-        libfmod::FMOD_STUDIO_USER_PROPERTY {
+        Ok(libfmod::FMOD_STUDIO_USER_PROPERTY {
             name,
             type_,
             __bindgen_anon_1,
-        }
+        })
     }
 }
 
 impl crate::wrap::WrapFMOD<RStruct> for libfmod::FMOD_STUDIO_USER_PROPERTY {
     fn wrap_fmod(self) -> RStruct {
+        use crate::wrap::WrapFMOD;
         use magnus::{Module, RClass, RModule};
 
         let rstruct = magnus::class::object()
@@ -142,12 +150,7 @@ impl crate::wrap::WrapFMOD<RStruct> for libfmod::FMOD_STUDIO_USER_PROPERTY {
             .const_get::<_, RClass>("UserProperty")
             .unwrap();
 
-        let name = unsafe {
-            std::ffi::CStr::from_ptr(self.name)
-                .to_str()
-                .unwrap()
-                .to_string()
-        };
+        let name: String = self.name.wrap_fmod();
         let type_ = self.type_;
 
         RStruct::from_value(
@@ -165,11 +168,9 @@ impl crate::wrap::WrapFMOD<RStruct> for libfmod::FMOD_STUDIO_USER_PROPERTY {
                         }
                         // FIXME: Oh my god this is wildly unsafe
                         libfmod::FMOD_STUDIO_USER_PROPERTY_TYPE::FMOD_STUDIO_USER_PROPERTY_TYPE_STRING => magnus::Value::from(
-                            std::ffi::CStr::from_ptr(self.__bindgen_anon_1.stringvalue)
-                                .to_str()
-                                .unwrap(),
+                            self.__bindgen_anon_1.stringvalue.wrap_fmod(),
                         ),
-                        libfmod::FMOD_STUDIO_USER_PROPERTY_TYPE::FMOD_STUDIO_USER_PROPERTY_TYPE_FORCEINT => unreachable!()
+                        libfmod::FMOD_STUDIO_USER_PROPERTY_TYPE::FMOD_STUDIO_USER_PROPERTY_TYPE_FORCEINT => *magnus::QNIL
                     }
                 }))
                 .unwrap(),
@@ -219,6 +220,265 @@ transparent_struct!(TimelineNestedBeatProperties, FMOD_STUDIO_TIMELINE_NESTED_BE
     properties, properties: RStruct
 ]);
 
+transparent_struct!(DspMeteringInfo, FMOD_DSP_METERING_INFO; [
+    numsamples, num_samples: i32,
+    peaklevel, peak_level: Vec<f32>,
+    rmslevel, rms_level: Vec<f32>,
+    numchannels, num_channels: i16
+]);
+
+fn bind_createsoundexinfo(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    module.const_set(
+        "CreateSoundExInfo",
+        magnus::r_struct::define_struct(
+            Some("CreateSoundExInfo"),
+            (
+                "length",
+                "file_offset",
+                "num_channels",
+                "default_frequency",
+                "format",
+                "decode_buffer_size",
+                "initial_subsound",
+                "num_subsounds",
+                "inclusion_list",
+                "encryption_key",
+                "max_polyphony",
+                "suggested_sound_type",
+                "file_buffer_size",
+                "initial_seek_position",
+                "initial_seek_pos_type",
+                "ignore_set_file_system",
+                "min_midi_granularity",
+                "non_block_thread_id",
+            ),
+        )?,
+    )
+}
+
+// FMOD_CREATESOUNDEXINFO holds pointers, so we have to keep what they point to alive alongside it.
+// Callbacks and other handles are not exposed and are left zeroed.
+pub(crate) struct CreateSoundExInfo {
+    pub info: libfmod::FMOD_CREATESOUNDEXINFO,
+    _encryption_key: Option<std::ffi::CString>,
+    _inclusion_list: Option<Vec<i32>>,
+}
+
+impl Default for CreateSoundExInfo {
+    fn default() -> Self {
+        // FMOD treats 0 as "use the default" for every field.
+        let mut info: libfmod::FMOD_CREATESOUNDEXINFO = unsafe { std::mem::zeroed() };
+        info.cbsize = std::mem::size_of::<libfmod::FMOD_CREATESOUNDEXINFO>() as _;
+
+        Self {
+            info,
+            _encryption_key: None,
+            _inclusion_list: None,
+        }
+    }
+}
+
+impl crate::wrap::TryUnwrapFMOD<CreateSoundExInfo> for RStruct {
+    fn try_unwrap_fmod(self) -> Result<CreateSoundExInfo, magnus::Error> {
+        use crate::wrap::{field_type_error, UnwrapFMOD};
+
+        // Every field is optional.
+        let CreateSoundExInfo { mut info, .. } = CreateSoundExInfo::default();
+
+        macro_rules! field {
+            ($c_member:ident, $member:literal) => {
+                if let Some(value) = self
+                    .aref::<_, Option<_>>($member)
+                    .map_err(field_type_error("CreateSoundExInfo", $member))?
+                {
+                    info.$c_member = value;
+                }
+            };
+        }
+
+        field!(length, "length");
+        field!(fileoffset, "file_offset");
+        field!(numchannels, "num_channels");
+        field!(defaultfrequency, "default_frequency");
+        field!(decodebuffersize, "decode_buffer_size");
+        field!(initialsubsound, "initial_subsound");
+        field!(numsubsounds, "num_subsounds");
+        field!(maxpolyphony, "max_polyphony");
+        field!(filebuffersize, "file_buffer_size");
+        field!(initialseekposition, "initial_seek_position");
+        field!(initialseekpostype, "initial_seek_pos_type");
+        field!(minmidigranularity, "min_midi_granularity");
+        field!(nonblockthreadid, "non_block_thread_id");
+
+        if let Some(format) = self
+            .aref::<_, Option<&crate::enums::SoundFormat>>("format")
+            .map_err(field_type_error("CreateSoundExInfo", "format"))?
+        {
+            info.format = format.unwrap_fmod();
+        }
+        if let Some(type_) = self
+            .aref::<_, Option<&crate::enums::SoundType>>("suggested_sound_type")
+            .map_err(field_type_error(
+                "CreateSoundExInfo",
+                "suggested_sound_type",
+            ))?
+        {
+            info.suggestedsoundtype = type_.unwrap_fmod();
+        }
+        if let Some(ignore) = self
+            .aref::<_, Option<bool>>("ignore_set_file_system")
+            .map_err(field_type_error(
+                "CreateSoundExInfo",
+                "ignore_set_file_system",
+            ))?
+        {
+            info.ignoresetfilesystem = ignore as _;
+        }
+
+        let mut inclusion_list = self
+            .aref::<_, Option<Vec<i32>>>("inclusion_list")
+            .map_err(field_type_error("CreateSoundExInfo", "inclusion_list"))?;
+        if let Some(list) = inclusion_list.as_mut() {
+            info.inclusionlist = list.as_mut_ptr();
+            info.inclusionlistnum = list.len() as _;
+        }
+
+        let encryption_key = self
+            .aref::<_, Option<String>>("encryption_key")
+            .map_err(field_type_error("CreateSoundExInfo", "encryption_key"))?
+            .map(std::ffi::CString::new)
+            .transpose()
+            .map_err(|e| magnus::Error::new(magnus::exception::arg_error(), e.to_string()))?;
+        if let Some(key) = encryption_key.as_ref() {
+            info.encryptionkey = key.as_ptr();
+        }
+
+        Ok(CreateSoundExInfo {
+            info,
+            _encryption_key: encryption_key,
+            _inclusion_list: inclusion_list,
+        })
+    }
+}
+
+// Used for the CreateSoundExInfo FMOD hands back to us (i.e in SoundInfo).
+// Pointers (the inclusion list and encryption key) and callbacks are not exposed, and are nil.
+impl crate::wrap::WrapFMOD<RStruct> for libfmod::FMOD_CREATESOUNDEXINFO {
+    fn wrap_fmod(self) -> RStruct {
+        use crate::wrap::WrapFMOD;
+        use magnus::{Module, RClass, RModule, Value, QNIL};
+
+        let rstruct = magnus::class::object()
+            .const_get::<_, RModule>("FMOD")
+            .unwrap()
+            .const_get::<_, RModule>("Struct")
+            .unwrap()
+            .const_get::<_, RClass>("CreateSoundExInfo")
+            .unwrap();
+
+        let fields: [Value; 18] = [
+            Value::from(self.length),
+            Value::from(self.fileoffset),
+            Value::from(self.numchannels),
+            Value::from(self.defaultfrequency),
+            Value::from(self.format.wrap_fmod()),
+            Value::from(self.decodebuffersize),
+            Value::from(self.initialsubsound),
+            Value::from(self.numsubsounds),
+            *QNIL,
+            *QNIL,
+            Value::from(self.maxpolyphony),
+            Value::from(self.suggestedsoundtype.wrap_fmod()),
+            Value::from(self.filebuffersize),
+            Value::from(self.initialseekposition),
+            Value::from(self.initialseekpostype),
+            Value::from(self.ignoresetfilesystem != 0),
+            Value::from(self.minmidigranularity),
+            Value::from(self.nonblockthreadid),
+        ];
+
+        RStruct::from_value(rstruct.new_instance(fields.as_slice()).unwrap()).unwrap()
+    }
+}
+
+fn bind_soundinfo(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    module.const_set(
+        "SoundInfo",
+        magnus::r_struct::define_struct(
+            Some("SoundInfo"),
+            ("name_or_data", "mode", "exinfo", "subsound_index"),
+        )?,
+    )
+}
+
+// A SoundInfo can be passed straight to Core::System#create_sound.
+// Sounds in banks loaded from memory are FMOD_OPENMEMORY_POINT and point into the bank, which we do not copy.
+// name_or_data is nil for those, and Studio::System#get_sound_info fills in the bank's String if it has it.
+impl crate::wrap::WrapFMOD<RStruct> for libfmod::FMOD_STUDIO_SOUND_INFO {
+    fn wrap_fmod(self) -> RStruct {
+        use crate::wrap::WrapFMOD;
+        use magnus::{Module, RClass, RModule, RString, Value, QNIL};
+
+        let rstruct = magnus::class::object()
+            .const_get::<_, RModule>("FMOD")
+            .unwrap()
+            .const_get::<_, RModule>("Struct")
+            .unwrap()
+            .const_get::<_, RClass>("SoundInfo")
+            .unwrap();
+
+        let name_or_data = if self.mode
+            & (libfmod::ffi::FMOD_OPENMEMORY | libfmod::ffi::FMOD_OPENMEMORY_POINT)
+            != 0
+        {
+            *QNIL
+        } else {
+            Value::from(RString::new(&self.name_or_data.wrap_fmod()))
+        };
+
+        RStruct::from_value(
+            rstruct
+                .new_instance((
+                    name_or_data,
+                    self.mode,
+                    Value::from(self.exinfo.wrap_fmod()),
+                    self.subsoundindex,
+                ))
+                .unwrap(),
+        )
+        .unwrap()
+    }
+}
+
+fn bind_plugininstanceproperties(module: impl magnus::Module) -> Result<(), magnus::Error> {
+    module.const_set(
+        "PluginInstanceProperties",
+        magnus::r_struct::define_struct(Some("PluginInstanceProperties"), ("name", "dsp"))?,
+    )
+}
+
+// Passed to PLUGIN_CREATED and PLUGIN_DESTROYED event callbacks.
+// The DSP is only valid until PLUGIN_DESTROYED returns.
+impl crate::wrap::WrapFMOD<RStruct> for libfmod::FMOD_STUDIO_PLUGIN_INSTANCE_PROPERTIES {
+    fn wrap_fmod(self) -> RStruct {
+        use crate::wrap::WrapFMOD;
+        use magnus::{Module, RClass, RModule};
+
+        let rstruct = magnus::class::object()
+            .const_get::<_, RModule>("FMOD")
+            .unwrap()
+            .const_get::<_, RModule>("Struct")
+            .unwrap()
+            .const_get::<_, RClass>("PluginInstanceProperties")
+            .unwrap();
+
+        let name: String = self.name.wrap_fmod();
+        let dsp: crate::core::dsp::Dsp = libfmod::Dsp::from(self.dsp).wrap_fmod();
+
+        RStruct::from_value(rstruct.new_instance((name, dsp)).unwrap()).unwrap()
+    }
+}
+
 pub fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
     let module = module.define_module("Struct")?;
 
@@ -238,6 +498,10 @@ pub fn bind(module: impl magnus::Module) -> Result<(), magnus::Error> {
     bind_timelinebeatproperties(module)?;
     bind_timelinemarkerproperties(module)?;
     bind_timelinenestedbeatproperties(module)?;
+    bind_createsoundexinfo(module)?;
+    bind_dspmeteringinfo(module)?;
+    bind_soundinfo(module)?;
+    bind_plugininstanceproperties(module)?;
 
     Ok(())
 }
@@ -2,3 +2,9 @@
 
 require_relative "libfmod/libfmod_ext"
 require_relative "libfmod/version"
+require_relative "libfmod/error"
+require_relative "libfmod/localization"
+require_relative "libfmod/callback_errors"
+# Only there when the FMOD SDK it was built against has fsbank.
+require_relative "libfmod/fsbank" if defined?(FMOD::FSBank)
+require_relative "libfmod/bank_load"
@@ -0,0 +1,75 @@
+# frozen_string_literal: true
+
+module FMOD
+  module Studio
+    # A bank being loaded in the background, returned by System#load_bank_file with LoadBankFlags::NONBLOCKING.
+    #
+    #   loads = paths.map { |path| system.load_bank_file(path, FMOD::Studio::LoadBankFlags::NONBLOCKING) }
+    #   until loads.all?(&:ready?)
+    #     system.update
+    #     draw_progress(loads.count(&:ready?), loads.size)
+    #   end
+    #
+    # The block passed to load_bank_file is called with the bank (or the error it failed with) once it has finished
+    # loading. That happens from System#update (or #wait), on the thread calling it.
+    class BankLoad
+      attr_reader :system, :bank, :error
+
+      def initialize(system, bank, on_complete = nil)
+        @system = system
+        @bank = bank
+        @on_complete = on_complete
+        @completed = false
+      end
+
+      # The bank's LoadingState. A bank that failed to load is in LoadingState::Error, and #error is why.
+      def state
+        return Enum::LoadingState::Error if @error
+
+        @bank.get_loading_state
+      rescue Error => e
+        @error = e
+        Enum::LoadingState::Error
+      end
+
+      # Whether the bank has finished loading, successfully or not.
+      def ready?
+        [Enum::LoadingState::Loaded, Enum::LoadingState::Error].include?(state)
+      end
+
+      def loaded?
+        state == Enum::LoadingState::Loaded
+      end
+
+      # Waits up to timeout seconds (forever if nil) for the bank to finish loading, and returns whether it has.
+      # FMOD only loads banks during System#update when it was initialized with SYNCHRONOUS_UPDATE, so this updates
+      # the system while it waits.
+      def wait(timeout = nil)
+        deadline = Process.clock_gettime(Process::CLOCK_MONOTONIC) + timeout if timeout
+
+        until ready?
+          return false if deadline && Process.clock_gettime(Process::CLOCK_MONOTONIC) >= deadline
+
+          @system.update
+          sleep(0.001)
+        end
+
+        poll
+      end
+
+      # Runs the block once the bank has finished loading, and returns whether it has. Called by System#update.
+      def poll
+        return true if @completed
+        return false unless ready?
+
+        @completed = true
+        @on_complete&.call(@error || @bank)
+        true
+      end
+
+      def inspect
+        "#<#{self.class} #{state}>"
+      end
+    end
+  end
+end
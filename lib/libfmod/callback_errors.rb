@@ -0,0 +1,27 @@
+# frozen_string_literal: true
+
+module FMOD
+  # What to do with exceptions raised by callbacks. Callbacks run on the event thread, so by default
+  # their exceptions are printed with Kernel#warn and otherwise dropped.
+  #
+  #   FMOD.callback_error_policy = :print                  # print them (the default)
+  #   FMOD.callback_error_policy = :raise                  # re-raise them from the next System#update
+  #   FMOD.callback_error_policy = Logger.new($stderr)     # anything with #error, like a Logger
+  #   FMOD.callback_error_policy = ->(e) { errors << e }   # anything with #call
+  @callback_error_policy = :print
+  # Exceptions waiting to be raised by System#update when the policy is :raise.
+  @pending_callback_errors = []
+
+  class << self
+    attr_reader :callback_error_policy
+
+    def callback_error_policy=(policy)
+      unless %i[print raise].include?(policy) || policy.respond_to?(:call) || policy.respond_to?(:error)
+        raise ArgumentError, "callback error policy must be :print, :raise, a logger or respond to #call"
+      end
+
+      @pending_callback_errors.clear unless policy == :raise
+      @callback_error_policy = policy
+    end
+  end
+end
@@ -0,0 +1,15 @@
+# frozen_string_literal: true
+
+module FMOD
+  # Errors raised from one of FMOD's own threads are created by Ruby from just a message, once they reach a Ruby
+  # thread. Those don't know which function failed, but every subclass knows its result code.
+  class Error
+    def code
+      @code || self.class::CODE
+    end
+
+    def result
+      @result || Enum::Result.new(code)
+    end
+  end
+end
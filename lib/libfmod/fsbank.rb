@@ -0,0 +1,48 @@
+# frozen_string_literal: true
+
+module FMOD
+  # Builds FSB sample banks.
+  #
+  #   FMOD::FSBank.init(FMOD::FSBank::InitFlags::GENERATEPROGRESSITEMS, 0, nil)
+  #   FMOD::FSBank.build(["media/line_1.ogg", "media/line_2.ogg"],
+  #                      format: FMOD::Enum::FSBankFormat::Vorbis, quality: 50,
+  #                      output: "media/lines.fsb") do |item|
+  #     puts "#{item.subsound_index}: #{item.state}"
+  #   end
+  #   FMOD::FSBank.release
+  module FSBank
+    class << self
+      alias build_without_progress build
+
+      # Same as the extension's build, but with a block it's passed every progress item while the bank builds.
+      # Progress items are only generated when FSBank was initialized with InitFlags::GENERATEPROGRESSITEMS.
+      def build(subsounds, **options, &block)
+        return build_without_progress(subsounds, **options) unless block
+
+        builder = Thread.new { build_without_progress(subsounds, **options) }
+        builder.report_on_exception = false
+        each_progress_item(&block) until builder.join(0.01)
+        each_progress_item(&block)
+
+        builder.value
+      end
+
+      def each_progress_item
+        while (item = next_progress_item)
+          yield item
+        end
+      end
+    end
+
+    # Works like FMOD::Error (see libfmod/error.rb).
+    class Error
+      def code
+        @code || self.class::CODE
+      end
+
+      def result
+        @result || Enum::FSBankResult.new(code)
+      end
+    end
+  end
+end
@@ -0,0 +1,115 @@
+# frozen_string_literal: true
+
+module FMOD
+  module Studio
+    # Keeps one localized bank loaded at a time and swaps between them at runtime.
+    #
+    #   localization = FMOD::Studio::Localization.new(system, events: ["event:/Character/Radio/Command"])
+    #   localization.register(:en, "media/Dialogue_EN.bank")
+    #   localization.register(:jp, "media/Dialogue_JP.bank")
+    #   localization.switch(:en)
+    #
+    #   sound, subsound_index = localization.create_sound("welcome")
+    #
+    # `events` are the paths of the events that play localized dialogue.
+    # Their instances are waited on (or stopped) before a locale's bank is unloaded,
+    # so they never lose their sound mid-line.
+    class Localization
+      attr_reader :system, :locale, :bank
+
+      def initialize(system, events: [], flags: 0)
+        @system = system
+        @events = events
+        @flags = flags
+        @paths = {}
+        @locale = nil
+        @bank = nil
+      end
+
+      # Registers the bank file to load for a locale.
+      def register(locale, path)
+        @paths[locale] = path
+
+        self
+      end
+
+      def locales
+        @paths.keys
+      end
+
+      # Switches to another locale, unloading the current locale's bank and loading the new one.
+      #
+      # If stop_mode (an FMOD::Enum::StopMode) is given, playing dialogue is stopped with it.
+      # Either way this updates the system until all dialogue has stopped, for at most timeout seconds.
+      # Returns false (and keeps the current locale) if it is still playing by then.
+      def switch(locale, stop_mode: nil, timeout: 5.0)
+        path = @paths.fetch(locale) { raise ArgumentError, "no bank registered for locale #{locale.inspect}" }
+        return true if locale == @locale
+
+        if @bank
+          instances = dialogue_instances
+          instances.each { |instance| instance.stop(stop_mode) } if stop_mode
+          return false unless wait_until_stopped(instances, timeout)
+
+          @bank.unload
+          @bank = nil
+          @locale = nil
+        end
+
+        @bank = @system.load_bank_file(path, @flags)
+        @locale = locale
+
+        true
+      end
+
+      # Looks up an audio table key in the active locale's bank. Returns an FMOD::Struct::SoundInfo.
+      def sound_info(key)
+        ensure_active!
+
+        @system.get_sound_info(key)
+      end
+
+      # Opens the sound for an audio table key in the active locale's bank.
+      # Returns the sound and the subsound index to play, ready to be assigned to ProgrammerSoundProperties.
+      def create_sound(key, mode = FMOD::Core::Mode::DEFAULT)
+        ensure_active!
+
+        @system.create_sound_by_key(key, mode)
+      end
+
+      # Unloads the active locale's bank, if there is one.
+      def unload
+        @bank&.unload
+        @bank = nil
+        @locale = nil
+      end
+
+      private
+
+      def ensure_active!
+        raise "no locale is active, call switch first" unless @bank
+      end
+
+      def dialogue_instances
+        @events.flat_map { |path| @system.get_event(path).get_instance_list }
+      end
+
+      def stopped?(instance)
+        !instance.is_valid || instance.get_playback_state == FMOD::Enum::PlaybackState::Stopped
+      end
+
+      def wait_until_stopped(instances, timeout)
+        deadline = Process.clock_gettime(Process::CLOCK_MONOTONIC) + timeout
+
+        until instances.all? { |instance| stopped?(instance) }
+          return false if Process.clock_gettime(Process::CLOCK_MONOTONIC) > deadline
+
+          @system.update
+          sleep(1.0 / 60.0)
+        end
+
+        true
+      end
+    end
+  end
+end
@@ -14,7 +14,7 @@
 //
 // You should have received a copy of the GNU General Public License
 // along with libfmod-rb.  If not, see <http://www.gnu.org/licenses/>.
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn find_fmod_dir() -> PathBuf {
     #[cfg(windows)]
@@ -48,71 +48,155 @@ fn find_fmod_dir() -> PathBuf {
     }
     #[cfg(not(windows))]
     {
-        todo!()
+        if let Some(path) = std::env::var_os("LIBFMOD_FMOD_API_DIR") {
+            let path = PathBuf::from(path);
+            if path.exists() {
+                return path;
+            }
+        }
+
+        // The SDK archives extract to a versioned directory (e.g. `fmodstudioapi20215linux`),
+        // so we search a few well known parent directories for anything that looks like one.
+        let mut search_dirs = vec![
+            PathBuf::from("."),
+            PathBuf::from("/opt"),
+            PathBuf::from("/usr/local"),
+            PathBuf::from("/usr/local/lib"),
+        ];
+        if let Some(home) = std::env::var_os("HOME") {
+            search_dirs.push(PathBuf::from(home));
+        }
+        // The macOS installer dmg is usually copied somewhere as-is.
+        #[cfg(target_os = "macos")]
+        search_dirs.push(PathBuf::from("/Applications"));
+
+        for dir in search_dirs {
+            for name in [
+                "FMOD Programmers API",
+                "FMOD SoundSystem",
+                "fmod",
+                "fmodstudioapi",
+            ] {
+                let path = dir.join(name);
+                if path.join("api").exists() {
+                    return path;
+                }
+            }
+
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut candidates: Vec<_> = entries
+                .filter_map(Result::ok)
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with("fmodstudioapi"))
+                        && p.join("api").exists()
+                })
+                .collect();
+            // Prefer the newest SDK if several are installed side by side.
+            candidates.sort();
+            if let Some(path) = candidates.pop() {
+                return path;
+            }
+        }
+
+        panic!("unable to find fmod api dir. please set LIBFMOD_FMOD_API_DIR to the path of fmod")
+    }
+}
+
+// FMOD ships one directory per architecture on Windows and Linux, and universal binaries on macOS.
+fn lib_arch_dir() -> Option<&'static str> {
+    let os = std::env::var("CARGO_CFG_TARGET_OS").unwrap();
+    let arch = std::env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+
+    match (os.as_str(), arch.as_str()) {
+        ("macos", _) => None,
+        ("windows", "x86") => Some("x86"),
+        ("windows", "x86_64") => Some("x64"),
+        ("windows", "aarch64") => Some("arm64"),
+        (_, "x86") => Some("x86"),
+        (_, "x86_64") => Some("x86_64"),
+        (_, "arm") => Some("arm"),
+        (_, "aarch64") => Some("arm64"),
+        (os, arch) => panic!("unsupported fmod target {os}/{arch}"),
     }
 }
 
+// Whether dir has the library the linker would pick for `-l name`.
+fn has_lib(dir: &Path, name: &str) -> bool {
+    let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap();
+    let files = match target_os.as_str() {
+        "windows" => vec![format!("{name}.lib"), format!("lib{name}.a")],
+        "macos" => vec![format!("lib{name}.dylib"), format!("lib{name}.a")],
+        _ => vec![format!("lib{name}.so"), format!("lib{name}.a")],
+    };
+
+    files.iter().any(|file| dir.join(file).exists())
+}
+
 fn main() {
     let api_dir = find_fmod_dir().join("api");
     let api_dir = api_dir.to_str().unwrap();
 
+    println!("cargo:rerun-if-env-changed=LIBFMOD_FMOD_API_DIR");
     println!("cargo:rerun-if-changed=\"{api_dir}/core/inc\"");
     println!("cargo:rerun-if-changed=\"{api_dir}/studio/inc\"");
     println!("cargo:rerun-if-changed=\"{api_dir}/fsbank/inc\"");
 
-    let bindgen = bindgen::builder()
+    let mut bindgen = bindgen::builder()
         .parse_callbacks(Box::new(bindgen::CargoCallbacks))
         .clang_arg(format!("-I{api_dir}/core/inc"))
         .clang_arg(format!("-I{api_dir}/studio/inc"))
         .clang_arg(format!("-I{api_dir}/fsbank/inc"))
         .rustified_enum("FMOD_.*")
+        .rustified_enum("FSBANK_.*")
         .header("src/wrapper.h");
 
-    #[cfg(target_arch = "x86")]
-    {
-        println!("cargo:rustc-link-search={api_dir}/core/lib/x86");
-        println!("cargo:rustc-link-search={api_dir}/studio/lib/x86");
-        println!("cargo:rustc-link-search={api_dir}/fsbank/lib/x86");
-    }
-    #[cfg(target_arch = "x86_64")]
-    {
-        println!("cargo:rustc-link-search={api_dir}/core/lib/x64");
-        println!("cargo:rustc-link-search={api_dir}/studio/lib/x64");
-        println!("cargo:rustc-link-search={api_dir}/fsbank/lib/x64");
-    }
-    #[cfg(any(debug_assertions, feature = "force-debug"))]
-    {
-        #[cfg(target_env = "msvc")]
-        {
-            println!("cargo:rustc-link-lib=fmodL_vc");
-            println!("cargo:rustc-link-lib=fmodstudioL_vc");
-        }
-        #[cfg(target_env = "gnu")]
-        {
-            println!("cargo:rustc-link-lib=fmodL");
-            println!("cargo:rustc-link-lib=fmodstudioL");
-        }
-    }
-    #[cfg(not(any(debug_assertions, feature = "force-debug")))]
-    {
-        #[cfg(target_env = "msvc")]
-        {
-            println!("cargo:rustc-link-lib=fmod_vc");
-            println!("cargo:rustc-link-lib=fmodstudio_vc");
+    let target_env = std::env::var("CARGO_CFG_TARGET_ENV").unwrap();
+    let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap();
+    // Only the msvc builds of FMOD have a suffix.
+    let suffix = if target_env == "msvc" { "_vc" } else { "" };
+    // Logging versions of the libraries.
+    let logging = if cfg!(any(debug_assertions, feature = "force-debug")) {
+        "L"
+    } else {
+        ""
+    };
+
+    let mut lib_dirs = vec![];
+    for (lib, name) in [
+        ("core", format!("fmod{logging}{suffix}")),
+        ("studio", format!("fmodstudio{logging}{suffix}")),
+        ("fsbank", format!("fsbank{suffix}")),
+    ] {
+        let mut dir = PathBuf::from(format!("{api_dir}/{lib}/lib"));
+        if let Some(arch) = lib_arch_dir() {
+            dir.push(arch);
         }
-        #[cfg(target_env = "gnu")]
-        {
-            println!("cargo:rustc-link-lib=fmod");
-            println!("cargo:rustc-link-lib=fmodstudio");
+        // Not every platform ships fsbank.
+        if lib == "fsbank" {
+            if !has_lib(&dir, &name) {
+                continue;
+            }
+
+            // Tells wrapper.h to include fsbank, and dependents (as DEP_FMOD_FSBANK) to bind it.
+            bindgen = bindgen.clang_arg("-DLIBFMOD_FSBANK");
+            println!("cargo:fsbank=1");
         }
+
+        println!("cargo:rustc-link-search={}", dir.display());
+        println!("cargo:rustc-link-lib={name}");
+        lib_dirs.push(dir);
     }
-    #[cfg(target_env = "msvc")]
-    {
-        println!("cargo:rustc-link-lib=fsbank_vc");
-    }
-    #[cfg(target_env = "gnu")]
-    {
-        println!("cargo:rustc-link-lib=fsbank");
+
+    // Shared objects are not copied next to the extension, so on Linux and macOS we pass the library
+    // directories to dependents (as DEP_FMOD_LIB_DIRS) so they can embed them as an rpath.
+    if target_os != "windows" {
+        let lib_dirs = std::env::join_paths(lib_dirs).expect("invalid fmod library path");
+        println!("cargo:lib_dirs={}", lib_dirs.to_str().unwrap());
     }
 
     let bindings = bindgen.generate().expect("failed to generate bindings");
@@ -31,6 +31,8 @@
 #include <fmod_studio.h>
 #include <fmod_studio_common.h>
 
-// FSBank
+// FSBank, if this platform has it (see build.rs)
+#ifdef LIBFMOD_FSBANK
 #include <fsbank.h>
-#include <fsbank_errors.h>
\ No newline at end of file
+#include <fsbank_errors.h>
+#endif
\ No newline at end of file
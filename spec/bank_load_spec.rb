@@ -0,0 +1,40 @@
+# frozen_string_literal: true
+
+require "libfmod"
+
+describe FMOD::Studio::BankLoad do
+  before do
+    @system = FMOD::Studio::System.create
+    @system.init(64, 0, 0)
+  end
+
+  after do
+    @system.release
+  end
+
+  it "loads banks in the background" do
+    completed = []
+    load = @system.load_bank_file("media/Master.bank", FMOD::Studio::LoadBankFlags::NONBLOCKING) do |bank|
+      completed << bank
+    end
+
+    expect(load).to be_a(FMOD::Studio::BankLoad)
+    expect(load.wait(5)).to be(true)
+    expect(load.state).to eq(FMOD::Enum::LoadingState::Loaded)
+    expect(completed).to eq([load.bank])
+  end
+
+  it "runs the block from update" do
+    completed = false
+    load = @system.load_bank_file("media/Master.bank", FMOD::Studio::LoadBankFlags::NONBLOCKING) { completed = true }
+
+    @system.update until load.ready?
+    @system.update
+
+    expect(completed).to be(true)
+  end
+
+  it "still loads banks synchronously by default" do
+    expect(@system.load_bank_file("media/Master.bank", 0)).to be_a(FMOD::Studio::Bank)
+  end
+end
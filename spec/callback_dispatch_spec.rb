@@ -0,0 +1,58 @@
+# frozen_string_literal: true
+
+require "libfmod"
+
+describe "FMOD.callback_dispatch" do
+  after do
+    FMOD.callback_dispatch = :thread
+  end
+
+  it "uses the event thread by default" do
+    expect(FMOD.callback_dispatch).to eq(:thread)
+  end
+
+  it "rejects unknown modes" do
+    expect { FMOD.callback_dispatch = :fiber }.to raise_error(ArgumentError)
+  end
+
+  it "runs callbacks on the thread calling update" do
+    FMOD.callback_dispatch = :update
+
+    studio = FMOD::Studio::System.create
+    # FMOD_STUDIO_INIT_SYNCHRONOUS_UPDATE
+    studio.init(64, 2, 0)
+
+    threads = []
+    studio.set_callback(proc {
+      threads << Thread.current
+      0
+    }, 0xFFFFFFFF)
+    studio.update
+
+    expect(threads).not_to be_empty
+    expect(threads.uniq).to eq([Thread.current])
+  ensure
+    studio&.release
+  end
+
+  it "runs callbacks while waiting on FMOD's threads" do
+    FMOD.callback_dispatch = :update
+
+    studio = FMOD::Studio::System.create
+    studio.init(64, 0, 0)
+
+    calls = 0
+    # Called from FMOD's own thread, which then waits for us to run it.
+    studio.set_callback(proc {
+      calls += 1
+      0
+    }, 0xFFFFFFFF)
+
+    expect(studio.load_bank_file("media/Master.bank", 0)).to be_a(FMOD::Studio::Bank)
+    studio.flush_commands
+    studio.flush_sample_loading
+    expect(calls).to be_positive
+  ensure
+    studio&.release
+  end
+end
@@ -0,0 +1,61 @@
+# frozen_string_literal: true
+
+require "libfmod"
+
+describe "FMOD.callback_error_policy" do
+  after do
+    FMOD.callback_error_policy = :print
+  end
+
+  it "prints by default" do
+    expect(FMOD.callback_error_policy).to eq(:print)
+  end
+
+  it "accepts handlers and loggers" do
+    handler = ->(e) { e }
+    FMOD.callback_error_policy = handler
+    expect(FMOD.callback_error_policy).to eq(handler)
+
+    logger = Object.new
+    def logger.error(exception); end
+    FMOD.callback_error_policy = logger
+    expect(FMOD.callback_error_policy).to eq(logger)
+  end
+
+  it "rejects anything else" do
+    expect { FMOD.callback_error_policy = :ignore }.to raise_error(ArgumentError)
+  end
+
+  it "re-raises callback errors from Studio::System#update" do
+    FMOD.callback_error_policy = :raise
+
+    studio = FMOD::Studio::System.create
+    studio.init(64, 0, 0)
+    studio.set_callback(proc { raise "boom" }, 0xFFFFFFFF)
+
+    expect do
+      # Callbacks run on the event thread, so give it a few updates to catch up.
+      10.times do
+        studio.update
+        sleep 0.01
+      end
+    end.to raise_error(RuntimeError, "boom")
+  ensure
+    studio&.release
+  end
+
+  it "warns about callback errors on stderr by default" do
+    studio = FMOD::Studio::System.create
+    studio.init(64, 0, 0)
+    studio.set_callback(proc { raise "boom" }, 0xFFFFFFFF)
+
+    expect do
+      10.times do
+        studio.update
+        sleep 0.01
+      end
+    end.to output(/WARNING RUBY ERROR IN CALLBACK: .*boom/).to_stderr
+  ensure
+    studio&.release
+  end
+end
@@ -0,0 +1,44 @@
+# frozen_string_literal: true
+
+require "libfmod"
+
+describe FMOD::Core::DSP do
+  before do
+    @system = FMOD::Core::System.create
+    @system.init(32, 0)
+  end
+
+  after do
+    @system.release
+  end
+
+  it "creates dsps by type" do
+    dsp = @system.create_dsp_by_type(FMOD::Enum::DspType::Lowpass)
+
+    expect(dsp.get_type).to eq(FMOD::Enum::DspType::Lowpass)
+  end
+
+  it "sets and gets parameters" do
+    dsp = @system.create_dsp_by_type(FMOD::Enum::DspType::Echo)
+    dsp.set_parameter_float(FMOD::Enum::DspEcho::Delay.to_i, 250.0)
+
+    expect(dsp.get_parameter_float(FMOD::Enum::DspEcho::Delay.to_i)).to eq(250.0)
+  end
+
+  it "can be added to channel groups" do
+    master = @system.get_master_channel_group
+    dsp = @system.create_dsp_by_type(FMOD::Enum::DspType::Lowpass)
+    master.add_dsp(FMOD::Core::ChannelGroup::DSP_HEAD, dsp)
+
+    expect(master.get_dsp(FMOD::Core::ChannelGroup::DSP_HEAD)).to eq(dsp)
+  end
+
+  it "connects dsps together" do
+    lowpass = @system.create_dsp_by_type(FMOD::Enum::DspType::Lowpass)
+    echo = @system.create_dsp_by_type(FMOD::Enum::DspType::Echo)
+    connection = lowpass.add_input(echo, FMOD::Enum::DspConnectionType::Standard)
+
+    expect(connection.get_input).to eq(echo)
+    expect(connection.get_output).to eq(lowpass)
+  end
+end
@@ -0,0 +1,35 @@
+# frozen_string_literal: true
+
+require "libfmod"
+
+describe FMOD::Error do
+  before do
+    @system = FMOD::Studio::System.create
+    @system.init(64, 0, 0)
+  end
+
+  it "is a StandardError" do
+    expect(FMOD::Error.ancestors).to include(StandardError)
+    expect(FMOD::Error::FileNotFound.superclass).to eq(FMOD::Error)
+  end
+
+  it "raises the subclass for the result code" do
+    expect { @system.load_bank_file("media/does_not_exist.bank", 0) }.to raise_error(FMOD::Error::FileNotFound)
+  end
+
+  it "exposes the result code" do
+    @system.load_bank_file("media/does_not_exist.bank", 0)
+  rescue FMOD::Error => e
+    expect(e.result).to eq(FMOD::Enum::Result::FileNotfound)
+    expect(e.code).to eq(FMOD::Enum::Result::FileNotfound.to_i)
+  end
+
+  it "knows the result code of every subclass" do
+    expect(FMOD::Error::FileNotFound::CODE).to eq(FMOD::Enum::Result::FileNotfound.to_i)
+  end
+
+  it "raises TypeError naming the field for badly typed structs" do
+    id = FMOD::Struct::Guid.new("not a number", 0, 0, [0] * 8)
+    expect { @system.lookup_path(id) }.to raise_error(TypeError, /Guid#data_1/)
+  end
+end
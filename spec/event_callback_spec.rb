@@ -0,0 +1,33 @@
+# frozen_string_literal: true
+
+require "libfmod"
+
+describe "FMOD::Studio::EventDescription#set_callback" do
+  before do
+    @system = FMOD::Studio::System.create
+    @system.init(64, 0, 0)
+    @system.load_bank_file("media/Master.bank", 0)
+    @system.load_bank_file("media/Master.strings.bank", 0)
+    @system.load_bank_file("media/Vehicles.bank", 0)
+    @event = @system.get_event("event:/Vehicles/Ride-on Mower")
+  end
+
+  after do
+    @system.release
+  end
+
+  it "rejects unknown keywords" do
+    expect { @event.set_callback(proc { 0 }, 0, sync: true) }.to raise_error(ArgumentError)
+  end
+
+  it "runs deferred callbacks" do
+    types = Queue.new
+    @event.set_callback(proc { |_instance, type, _parameter| types << type },
+                        FMOD::Studio::EventCallbackType::CREATED, async: true)
+
+    @event.create_instance
+    @system.update
+
+    expect(types.pop).to eq(FMOD::Studio::EventCallbackType::CREATED)
+  end
+end
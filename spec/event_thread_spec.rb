@@ -0,0 +1,29 @@
+# frozen_string_literal: true
+
+require "libfmod"
+
+describe FMOD::EventThread do
+  it "reports callback stats" do
+    stats = FMOD.callback_stats
+    expect(stats.keys).to eq(%i[pending processed average_latency])
+    expect(stats[:pending]).to be >= 0
+    expect(stats[:average_latency]).to be_a(Float)
+  end
+
+  it "can be shut down and restarted" do
+    FMOD.shutdown_event_thread
+    expect(FMOD::EventThread).not_to be_alive
+
+    thread = FMOD.restart_event_thread
+    expect(thread).to be_alive
+    expect(FMOD::EventThread).to eq(thread)
+  end
+
+  it "survives being shut down more than once" do
+    2.times { FMOD.shutdown_event_thread }
+    FMOD.restart_event_thread
+    sleep(0.05)
+
+    expect(FMOD::EventThread).to be_alive
+  end
+end
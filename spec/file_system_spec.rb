@@ -0,0 +1,105 @@
+# frozen_string_literal: true
+
+require "libfmod"
+require "stringio"
+
+describe "custom file systems" do
+  before do
+    @system = FMOD::Studio::System.create
+    @system.init(64, 0, 0)
+  end
+
+  after do
+    @system.release
+  end
+
+  it "loads banks from any IO" do
+    io = StringIO.new(File.binread("media/Master.bank"))
+    bank = @system.load_bank_custom(io, 0)
+
+    expect(bank.is_valid).to be(true)
+    bank.unload
+    # FMOD reads from it until the bank has finished unloading, which update checks on.
+    expect(io).not_to be_closed
+
+    100.times do
+      @system.update
+      break if io.closed?
+
+      sleep 0.01
+    end
+    expect(io).to be_closed
+  end
+
+  it "loads banks from strings and freezes them" do
+    data = File.binread("media/Master.bank")
+    bank = @system.load_bank_custom(data, 0)
+
+    expect(bank.is_valid).to be(true)
+    expect(data).to be_frozen
+    bank.unload
+  end
+
+  it "loads banks from part of a string" do
+    bank = File.binread("media/Master.bank")
+    data = "header#{bank}footer"
+    bank = @system.load_bank_memory(data, FMOD::Enum::LoadMemoryMode::Memory, 0, 6, bank.bytesize)
+
+    expect(bank.is_valid).to be(true)
+    expect(data).not_to be_frozen
+    bank.unload
+  end
+
+  it "raises for out of range offsets" do
+    data = File.binread("media/Master.bank")
+    expect do
+      @system.load_bank_memory(data, FMOD::Enum::LoadMemoryMode::Memory, 0, data.bytesize, 1)
+    end.to raise_error(IndexError)
+  end
+
+  it "either points at MemoryPoint strings or raises" do
+    data = File.binread("media/Master.bank")
+
+    begin
+      bank = @system.load_bank_memory(data, FMOD::Enum::LoadMemoryMode::MemoryPoint, 0)
+    rescue ArgumentError => e
+      expect(e.message).to match(/aligned/)
+      next
+    end
+
+    expect(data).to be_frozen
+    GC.start
+    expect(bank.get_event_count).to be_a(Integer)
+    bank.unload
+  end
+
+  it "opens files through the file system handler" do
+    opened = []
+    handler = Object.new
+    handler.define_singleton_method(:open) do |name|
+      opened << name
+      File.open(name, "rb")
+    end
+
+    core = @system.get_core_system
+    core.set_file_system(handler, -1, false)
+    @system.load_bank_file("media/Master.bank", 0)
+    core.set_file_system(nil, -1, false)
+
+    expect(opened).to include("media/Master.bank")
+  end
+
+  it "only lets one system have a file system handler" do
+    other = FMOD::Core::System.create
+    other.init(32, 0)
+    handler = Object.new
+    core = @system.get_core_system
+
+    core.set_file_system(handler, -1, false)
+    expect { other.set_file_system(handler, -1, false) }.to raise_error(RuntimeError, /file system handler/)
+
+    core.set_file_system(nil, -1, false)
+    other.set_file_system(handler, -1, false)
+    other.release
+  end
+end
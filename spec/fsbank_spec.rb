@@ -0,0 +1,32 @@
+# frozen_string_literal: true
+
+require "libfmod"
+
+describe "FMOD::FSBank", if: defined?(FMOD::FSBank) do
+  before do
+    FMOD::FSBank.init(FMOD::FSBank::InitFlags::GENERATEPROGRESSITEMS, 0, nil)
+  end
+
+  after do
+    FMOD::FSBank.release
+  end
+
+  it "builds an FSB in memory" do
+    fsb = FMOD::FSBank.build(["media/640166main_MECO.ogg"], format: FMOD::Enum::FSBankFormat::Pcm)
+    expect(fsb.byteslice(0, 4)).to eq("FSB5")
+  end
+
+  it "passes progress items to the block" do
+    states = []
+    FMOD::FSBank.build(["media/640166main_MECO.ogg"], format: FMOD::Enum::FSBankFormat::Pcm) do |item|
+      states << item.state
+    end
+    expect(states).to include(FMOD::Enum::FSBankState::Finished)
+  end
+
+  it "raises its own errors" do
+    expect do
+      FMOD::FSBank.build(["media/does_not_exist.ogg"], format: FMOD::Enum::FSBankFormat::Pcm)
+    end.to raise_error(FMOD::FSBank::Error::FileNotFound)
+  end
+end
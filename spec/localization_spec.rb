@@ -0,0 +1,49 @@
+# frozen_string_literal: true
+
+require "libfmod"
+
+describe FMOD::Studio::Localization do
+  before do
+    @system = FMOD::Studio::System.create
+    @system.init(64, 0, 0)
+    @system.load_bank_file("media/Master.bank", 0)
+    @system.load_bank_file("media/Master.strings.bank", 0)
+
+    @localization = FMOD::Studio::Localization.new(@system)
+    @localization.register(:en, "media/Dialogue_EN.bank")
+    @localization.register(:jp, "media/Dialogue_JP.bank")
+    @localization.register(:cn, "media/Dialogue_CN.bank")
+  end
+
+  after do
+    @system.release
+  end
+
+  it "registers locales" do
+    expect(@localization.locales).to eq(%i[en jp cn])
+    expect(@localization.locale).to be_nil
+  end
+
+  it "switches between locales" do
+    expect(@localization.switch(:en)).to be(true)
+    en = @localization.bank
+
+    expect(@localization.switch(:jp, stop_mode: FMOD::Enum::StopMode::Immediate)).to be(true)
+    expect(@localization.locale).to eq(:jp)
+    expect(en.is_valid).to be(false)
+  end
+
+  it "resolves keys against the active locale" do
+    expect { @localization.sound_info("welcome") }.to raise_error(RuntimeError)
+
+    @localization.switch(:cn)
+    sound, subsound_index = @localization.create_sound("welcome")
+
+    expect(sound).to be_a(FMOD::Core::Sound)
+    expect(subsound_index).to be >= 0
+  end
+
+  it "rejects unregistered locales" do
+    expect { @localization.switch(:fr) }.to raise_error(ArgumentError)
+  end
+end
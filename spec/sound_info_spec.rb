@@ -0,0 +1,98 @@
+# frozen_string_literal: true
+
+require "fiddle"
+require "libfmod"
+
+describe FMOD::Studio::System do
+  before do
+    @system = FMOD::Studio::System.create
+    @system.init(64, 0, 0)
+    @system.load_bank_file("media/Master.bank", 0)
+    @system.load_bank_file("media/Master.strings.bank", 0)
+    @system.load_bank_file("media/Dialogue_EN.bank", 0)
+  end
+
+  after do
+    @system.release
+  end
+
+  describe "get_sound_info" do
+    it "looks up audio table keys" do
+      info = @system.get_sound_info("welcome")
+
+      expect(info).to be_a(FMOD::Struct::SoundInfo)
+      expect(info.exinfo).to be_a(FMOD::Struct::CreateSoundExInfo)
+      expect(info.subsound_index).to be >= 0
+    end
+
+    it "raises for unknown keys" do
+      expect { @system.get_sound_info("not a key") }.to raise_error(FMOD::Error::EventNotFound)
+    end
+
+    it "can be passed to Core::System#create_sound" do
+      info = @system.get_sound_info("welcome")
+      sound = @system.get_core_system.create_sound(info.name_or_data, info.mode, info.exinfo)
+
+      expect_same_sound(sound.get_sub_sound(info.subsound_index), @system)
+    end
+  end
+
+  # Compares a subsound against the one create_sound_by_key opens for "welcome".
+  def expect_same_sound(subsound, system)
+    expected, index = system.create_sound_by_key("welcome", 0)
+    expected = expected.get_sub_sound(index)
+
+    expect(subsound).to be_a(FMOD::Core::Sound)
+    expect(subsound.get_format).to eq(expected.get_format)
+    expect(subsound.get_length(FMOD::Core::TimeUnit::PCM)).to eq(expected.get_length(FMOD::Core::TimeUnit::PCM))
+    expect(subsound.get_length(FMOD::Core::TimeUnit::PCM)).to be_positive
+  end
+
+  describe "get_sound_info for banks loaded from memory" do
+    before do
+      @memory_system = FMOD::Studio::System.create
+      @memory_system.init(64, 0, 0)
+      @memory_system.load_bank_file("media/Master.bank", 0)
+      @memory_system.load_bank_file("media/Master.strings.bank", 0)
+      @data = File.binread("media/Dialogue_EN.bank")
+    end
+
+    after do
+      @memory_system.release
+    end
+
+    it "gives the pinned bank and where in it the sound is" do
+      # MemoryPoint needs the bank aligned, so it goes wherever in the buffer that is.
+      alignment = 32
+      buffer = "\0".b * (@data.bytesize + alignment)
+      offset = -Fiddle::Pointer[buffer].to_i % alignment
+      buffer[offset, @data.bytesize] = @data
+      @memory_system.load_bank_memory(buffer, FMOD::Enum::LoadMemoryMode::MemoryPoint, 0, offset, @data.bytesize)
+
+      info = @memory_system.get_sound_info("welcome")
+
+      expect(info.name_or_data).to equal(buffer)
+      expect(info.exinfo.file_offset).to be > offset
+      expect(info.exinfo.file_offset + info.exinfo.length).to be <= offset + @data.bytesize
+
+      sound = @memory_system.get_core_system.create_sound(info.name_or_data, info.mode, info.exinfo)
+      expect_same_sound(sound.get_sub_sound(info.subsound_index), @memory_system)
+    end
+
+    it "has no data for banks FMOD copied" do
+      @memory_system.load_bank_memory(@data, FMOD::Enum::LoadMemoryMode::Memory, 0)
+
+      expect(@memory_system.get_sound_info("welcome").name_or_data).to be_nil
+      expect(@memory_system.create_sound_by_key("welcome", 0).first).to be_a(FMOD::Core::Sound)
+    end
+  end
+
+  describe "create_sound_by_key" do
+    it "opens the sound for a key" do
+      sound, subsound_index = @system.create_sound_by_key("welcome", FMOD::Core::Mode::CREATECOMPRESSEDSAMPLE)
+
+      expect(sound).to be_a(FMOD::Core::Sound)
+      expect(subsound_index).to be >= 0
+    end
+  end
+end
@@ -0,0 +1,110 @@
+# frozen_string_literal: true
+
+require "libfmod"
+
+describe FMOD::Core::Sound do
+  before do
+    @system = FMOD::Core::System.create
+    @system.init(32, 0)
+  end
+
+  after do
+    @system.release
+  end
+
+  describe "create_sound" do
+    it "loads sounds from files" do
+      sound = @system.create_sound("media/640166main_MECO.ogg", FMOD::Core::Mode::DEFAULT, nil)
+      type, _format, channels, _bits = sound.get_format
+
+      expect(type).to eq(FMOD::Enum::SoundType::OggVorbis)
+      expect(channels).to be_positive
+      expect(sound.get_length(FMOD::Core::TimeUnit::MS)).to be_positive
+    end
+
+    it "loads sounds from memory" do
+      data = File.binread("media/640166main_MECO.ogg")
+      sound = @system.create_sound(data, FMOD::Core::Mode::OPENMEMORY, nil)
+
+      expect(sound.get_open_state.first).to eq(FMOD::Enum::OpenState::Ready)
+    end
+
+    it "copies data passed with OPENMEMORY_POINT" do
+      data = File.binread("media/640166main_MECO.ogg")
+      sound = @system.create_sound(data, FMOD::Core::Mode::OPENMEMORY_POINT, nil)
+
+      expect(sound.get_mode & FMOD::Core::Mode::OPENMEMORY_POINT).to eq(0)
+      expect(data).not_to be_frozen
+    end
+
+    it "creates user sounds from nothing but their exinfo" do
+      exinfo = FMOD::Struct::CreateSoundExInfo.new
+      exinfo.length = 44_100 * 2 * 2
+      exinfo.num_channels = 2
+      exinfo.default_frequency = 44_100
+      exinfo.format = FMOD::Enum::SoundFormat::Pcm16
+      sound = @system.create_sound(nil, FMOD::Core::Mode::OPENUSER, exinfo)
+
+      expect(sound.get_format[1]).to eq(FMOD::Enum::SoundFormat::Pcm16)
+      expect(sound.get_length(FMOD::Core::TimeUnit::PCMBYTES)).to eq(exinfo.length)
+    end
+
+    it "needs a name or data without OPENUSER" do
+      expect { @system.create_sound(nil, FMOD::Core::Mode::DEFAULT, nil) }.to raise_error(TypeError)
+    end
+  end
+
+  describe "create_stream" do
+    it "opens streams" do
+      sound = @system.create_stream("media/640166main_MECO.ogg", FMOD::Core::Mode::LOOP_NORMAL, nil)
+
+      expect(sound.get_mode & FMOD::Core::Mode::CREATESTREAM).not_to eq(0)
+    end
+  end
+
+  describe "play_sound" do
+    it "plays sounds on a channel" do
+      sound = @system.create_sound("media/640166main_MECO.ogg", FMOD::Core::Mode::DEFAULT, nil)
+      channel = @system.play_sound(sound, nil, true)
+      channel.set_volume(0.5)
+
+      expect(channel.get_paused).to be(true)
+      expect(channel.get_volume).to eq(0.5)
+      expect(channel.get_current_sound).to eq(sound)
+    end
+  end
+
+  describe "channel groups" do
+    it "can be nested under the master channel group" do
+      master = @system.get_master_channel_group
+      group = @system.create_channel_group("music")
+      master.add_group(group, true)
+
+      expect(group.get_name).to eq("music")
+      expect(group.get_parent_group).to eq(master)
+    end
+
+    it "can be released along with their user data" do
+      group = @system.create_channel_group("sfx")
+      group.set_user_data(:sfx)
+
+      expect(group.get_user_data).to eq(:sfx)
+      expect { group.release }.not_to raise_error
+    end
+  end
+
+  describe "channel user data" do
+    it "lasts until the channel ends" do
+      sound = @system.create_sound("media/640166main_MECO.ogg", FMOD::Core::Mode::DEFAULT, nil)
+      channel = @system.play_sound(sound, nil, true)
+      channel.set_user_data(:voice)
+
+      expect(channel.get_user_data).to eq(:voice)
+
+      channel.stop
+      @system.update
+
+      expect { channel.get_user_data }.to raise_error(FMOD::Error::InvalidHandle)
+    end
+  end
+end
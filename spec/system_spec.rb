@@ -21,6 +21,29 @@ describe FMOD::Studio::System do
       end
     end
 
+    describe "get_bank" do
+      it "returns handles usable as hash keys" do
+        bank = @system.load_bank_file("media/Master.bank", 0)
+        hash = { bank => 1 }
+        hash[@system.get_bank("bank:/Master")] = 2
+
+        expect(hash.size).to eq(1)
+        expect([bank, @system.get_bank("bank:/Master")].uniq.size).to eq(1)
+      end
+    end
+
+    describe "list getters" do
+      it "return as many elements as their counts" do
+        bank = @system.load_bank_file("media/Master.bank", 0)
+
+        expect(bank.get_event_list.size).to eq(bank.get_event_count)
+        expect(bank.get_bus_list.size).to eq(bank.get_bus_count)
+        expect(bank.get_vca_list.size).to eq(bank.get_vca_count)
+        expect(@system.get_bank_list).to eq([bank])
+        expect(@system.get_parameter_description_list.size).to eq(@system.get_parameter_description_count)
+      end
+    end
+
     describe "get_core_system" do
       it "can get the core system" do
         expect(@system.get_core_system)
@@ -34,4 +57,13 @@ describe FMOD::Studio::System do
       end
     end
   end
+
+  describe "release" do
+    it "releases systems that were never initialized" do
+      system = FMOD::Studio::System.create
+      system.set_user_data(:uninitialized)
+
+      expect { system.release }.not_to raise_error
+    end
+  end
 end